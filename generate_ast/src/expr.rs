@@ -1,15 +1,31 @@
 use crate::error::*;
 use crate::token::*;
 use crate::object::*;
+use crate::stmt::*;
+use crate::span::*;
+use crate::pattern::*;
+use crate::signature::*;
 use std::rc::Rc;
 
 pub enum Expr {
+    Array(Rc<ArrayExpr>),
     Assign(Rc<AssignExpr>),
     Binary(Rc<BinaryExpr>),
     Call(Rc<CallExpr>),
+    Get(Rc<GetExpr>),
     Grouping(Rc<GroupingExpr>),
+    Index(Rc<IndexExpr>),
+    IndexSet(Rc<IndexSetExpr>),
+    Interpolation(Rc<InterpolationExpr>),
+    Lambda(Rc<LambdaExpr>),
     Literal(Rc<LiteralExpr>),
     Logical(Rc<LogicalExpr>),
+    Map(Rc<MapExpr>),
+    Match(Rc<MatchExpr>),
+    Range(Rc<RangeExpr>),
+    Set(Rc<SetExpr>),
+    Super(Rc<SuperExpr>),
+    This(Rc<ThisExpr>),
     Unary(Rc<UnaryExpr>),
     Variable(Rc<VariableExpr>),
 }
@@ -17,67 +33,206 @@ pub enum Expr {
 impl Expr {
     pub fn accept<T>(&self, expr_visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
         match self {
+            Expr::Array(v) => v.accept(expr_visitor),
             Expr::Assign(v) => v.accept(expr_visitor),
             Expr::Binary(v) => v.accept(expr_visitor),
             Expr::Call(v) => v.accept(expr_visitor),
+            Expr::Get(v) => v.accept(expr_visitor),
             Expr::Grouping(v) => v.accept(expr_visitor),
+            Expr::Index(v) => v.accept(expr_visitor),
+            Expr::IndexSet(v) => v.accept(expr_visitor),
+            Expr::Interpolation(v) => v.accept(expr_visitor),
+            Expr::Lambda(v) => v.accept(expr_visitor),
             Expr::Literal(v) => v.accept(expr_visitor),
             Expr::Logical(v) => v.accept(expr_visitor),
+            Expr::Map(v) => v.accept(expr_visitor),
+            Expr::Match(v) => v.accept(expr_visitor),
+            Expr::Range(v) => v.accept(expr_visitor),
+            Expr::Set(v) => v.accept(expr_visitor),
+            Expr::Super(v) => v.accept(expr_visitor),
+            Expr::This(v) => v.accept(expr_visitor),
             Expr::Unary(v) => v.accept(expr_visitor),
             Expr::Variable(v) => v.accept(expr_visitor),
         }
     }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Array(v) => v.span,
+            Expr::Assign(v) => v.span,
+            Expr::Binary(v) => v.span,
+            Expr::Call(v) => v.span,
+            Expr::Get(v) => v.span,
+            Expr::Grouping(v) => v.span,
+            Expr::Index(v) => v.span,
+            Expr::IndexSet(v) => v.span,
+            Expr::Interpolation(v) => v.span,
+            Expr::Lambda(v) => v.span,
+            Expr::Literal(v) => v.span,
+            Expr::Logical(v) => v.span,
+            Expr::Map(v) => v.span,
+            Expr::Match(v) => v.span,
+            Expr::Range(v) => v.span,
+            Expr::Set(v) => v.span,
+            Expr::Super(v) => v.span,
+            Expr::This(v) => v.span,
+            Expr::Unary(v) => v.span,
+            Expr::Variable(v) => v.span,
+        }
+    }
 }
 
 pub trait ExprVisitor<T> {
+    fn visit_array_expr(&self, expr: &ArrayExpr) -> Result<T, LoxError>;
     fn visit_assign_expr(&self, expr: &AssignExpr) -> Result<T, LoxError>;
     fn visit_binary_expr(&self, expr: &BinaryExpr) -> Result<T, LoxError>;
     fn visit_call_expr(&self, expr: &CallExpr) -> Result<T, LoxError>;
+    fn visit_get_expr(&self, expr: &GetExpr) -> Result<T, LoxError>;
     fn visit_grouping_expr(&self, expr: &GroupingExpr) -> Result<T, LoxError>;
+    fn visit_index_expr(&self, expr: &IndexExpr) -> Result<T, LoxError>;
+    fn visit_index_set_expr(&self, expr: &IndexSetExpr) -> Result<T, LoxError>;
+    fn visit_interpolation_expr(&self, expr: &InterpolationExpr) -> Result<T, LoxError>;
+    fn visit_lambda_expr(&self, expr: &LambdaExpr) -> Result<T, LoxError>;
     fn visit_literal_expr(&self, expr: &LiteralExpr) -> Result<T, LoxError>;
     fn visit_logical_expr(&self, expr: &LogicalExpr) -> Result<T, LoxError>;
+    fn visit_map_expr(&self, expr: &MapExpr) -> Result<T, LoxError>;
+    fn visit_match_expr(&self, expr: &MatchExpr) -> Result<T, LoxError>;
+    fn visit_range_expr(&self, expr: &RangeExpr) -> Result<T, LoxError>;
+    fn visit_set_expr(&self, expr: &SetExpr) -> Result<T, LoxError>;
+    fn visit_super_expr(&self, expr: &SuperExpr) -> Result<T, LoxError>;
+    fn visit_this_expr(&self, expr: &ThisExpr) -> Result<T, LoxError>;
     fn visit_unary_expr(&self, expr: &UnaryExpr) -> Result<T, LoxError>;
     fn visit_variable_expr(&self, expr: &VariableExpr) -> Result<T, LoxError>;
 }
 
+pub struct ArrayExpr {
+    pub elements: Vec<Rc<Expr>>,
+    pub span: Span,
+}
+
 pub struct AssignExpr {
     pub name: Token,
     pub value: Rc<Expr>,
+    pub span: Span,
 }
 
 pub struct BinaryExpr {
     pub left: Rc<Expr>,
     pub operator: Token,
     pub right: Rc<Expr>,
+    pub span: Span,
 }
 
 pub struct CallExpr {
     pub callee: Rc<Expr>,
     pub paren: Token,
     pub arguments: Vec<Rc<Expr>>,
+    pub named: Vec<NamedArgument>,
+    pub span: Span,
+}
+
+pub struct GetExpr {
+    pub object: Rc<Expr>,
+    pub name: Token,
+    pub span: Span,
 }
 
 pub struct GroupingExpr {
     pub expression: Rc<Expr>,
+    pub span: Span,
+}
+
+pub struct IndexExpr {
+    pub object: Rc<Expr>,
+    pub bracket: Token,
+    pub index: Rc<Expr>,
+    pub span: Span,
+}
+
+pub struct IndexSetExpr {
+    pub object: Rc<Expr>,
+    pub bracket: Token,
+    pub index: Rc<Expr>,
+    pub value: Rc<Expr>,
+    pub span: Span,
+}
+
+pub struct InterpolationExpr {
+    pub parts: Vec<Rc<Expr>>,
+    pub span: Span,
+}
+
+pub struct LambdaExpr {
+    pub function: Rc<FunctionStmt>,
+    pub span: Span,
 }
 
 pub struct LiteralExpr {
     pub value: Option<Object>,
+    pub span: Span,
 }
 
 pub struct LogicalExpr {
     pub left: Rc<Expr>,
     pub operator: Token,
     pub right: Rc<Expr>,
+    pub span: Span,
+}
+
+pub struct MapExpr {
+    pub brace: Token,
+    pub keys: Vec<Rc<Expr>>,
+    pub values: Vec<Rc<Expr>>,
+    pub span: Span,
+}
+
+pub struct MatchExpr {
+    pub keyword: Token,
+    pub subject: Rc<Expr>,
+    pub arms: Vec<MatchArm<Rc<Expr>>>,
+    pub span: Span,
+}
+
+pub struct RangeExpr {
+    pub start: Rc<Expr>,
+    pub operator: Token,
+    pub end: Rc<Expr>,
+    pub span: Span,
+}
+
+pub struct SetExpr {
+    pub object: Rc<Expr>,
+    pub name: Token,
+    pub value: Rc<Expr>,
+    pub span: Span,
+}
+
+pub struct SuperExpr {
+    pub keyword: Token,
+    pub method: Token,
+    pub span: Span,
+}
+
+pub struct ThisExpr {
+    pub keyword: Token,
+    pub span: Span,
 }
 
 pub struct UnaryExpr {
     pub operator: Token,
     pub right: Rc<Expr>,
+    pub span: Span,
 }
 
 pub struct VariableExpr {
     pub name: Token,
+    pub span: Span,
+}
+
+impl ArrayExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_array_expr(self)
+    }
 }
 
 impl AssignExpr {
@@ -98,12 +253,42 @@ impl CallExpr {
     }
 }
 
+impl GetExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_get_expr(self)
+    }
+}
+
 impl GroupingExpr {
     pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
         visitor.visit_grouping_expr(self)
     }
 }
 
+impl IndexExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_index_expr(self)
+    }
+}
+
+impl IndexSetExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_index_set_expr(self)
+    }
+}
+
+impl InterpolationExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_interpolation_expr(self)
+    }
+}
+
+impl LambdaExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_lambda_expr(self)
+    }
+}
+
 impl LiteralExpr {
     pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
         visitor.visit_literal_expr(self)
@@ -116,6 +301,42 @@ impl LogicalExpr {
     }
 }
 
+impl MapExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_map_expr(self)
+    }
+}
+
+impl MatchExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_match_expr(self)
+    }
+}
+
+impl RangeExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_range_expr(self)
+    }
+}
+
+impl SetExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_set_expr(self)
+    }
+}
+
+impl SuperExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_super_expr(self)
+    }
+}
+
+impl ThisExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_this_expr(self)
+    }
+}
+
 impl UnaryExpr {
     pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxError> {
         visitor.visit_unary_expr(self)
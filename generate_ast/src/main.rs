@@ -16,36 +16,51 @@ pub fn generate_ast(output_dir: &str) -> io::Result<()> {
     define_ast(
         output_dir,
         "Expr",
-        &["error", "token", "object", "rc"],
+        &["error", "token", "object", "stmt", "span", "pattern", "signature", "rc"],
         &[
-            "Assign   : Token name, Rc<Expr> value",
-            "Binary   : Rc<Expr> left, Token operator, Rc<Expr> right",
-            "Call     : Rc<Expr> callee, Token paren, Vec<Rc<Expr>> arguments",
-            //"Get      : Rc<Expr> object, Token name",
-            "Grouping : Rc<Expr> expression",
-            "Literal  : Option<Object> value",
-            "Logical  : Rc<Expr> left, Token operator, Rc<Expr> right",
-            //"Set      : Rc<Expr> object, Token name, Rc<Expr> value",
-            "Unary    : Token operator, Rc<Expr> right",
-            "Variable : Token name",
+            "Array         : Vec<Rc<Expr>> elements",
+            "Assign        : Token name, Rc<Expr> value",
+            "Binary        : Rc<Expr> left, Token operator, Rc<Expr> right",
+            "Call          : Rc<Expr> callee, Token paren, Vec<Rc<Expr>> arguments, Vec<NamedArgument> named",
+            "Get           : Rc<Expr> object, Token name",
+            "Grouping      : Rc<Expr> expression",
+            "Index         : Rc<Expr> object, Token bracket, Rc<Expr> index",
+            "IndexSet      : Rc<Expr> object, Token bracket, Rc<Expr> index, Rc<Expr> value",
+            "Interpolation : Vec<Rc<Expr>> parts",
+            "Lambda        : Rc<FunctionStmt> function",
+            "Literal       : Option<Object> value",
+            "Logical       : Rc<Expr> left, Token operator, Rc<Expr> right",
+            "Map           : Token brace, Vec<Rc<Expr>> keys, Vec<Rc<Expr>> values",
+            "Match         : Token keyword, Rc<Expr> subject, Vec<MatchArm<Rc<Expr>>> arms",
+            "Range         : Rc<Expr> start, Token operator, Rc<Expr> end",
+            "Set           : Rc<Expr> object, Token name, Rc<Expr> value",
+            "Super         : Token keyword, Token method",
+            "This          : Token keyword",
+            "Unary         : Token operator, Rc<Expr> right",
+            "Variable      : Token name",
         ],
     )?;
 
     define_ast(
     output_dir,
     "Stmt",
-    &["error", "expr", "token", "rc"],
+    &["error", "expr", "token", "span", "pattern", "signature", "rc"],
     &[
         "Block      : Rc<Vec<Rc<Stmt>>> statements",
-        "Break      : Token token",
-        "Continue   : Token token",
+        "Break      : Token token, Option<Token> label",
+        "Class      : Token name, Option<Rc<Expr>> superclass, Vec<Token> modifiers, Vec<Rc<FunctionStmt>> methods, Vec<Rc<VarStmt>> fields",
+        "Continue   : Token token, Option<Token> label",
+        "DoWhile    : Rc<Stmt> body, Rc<Expr> condition, Option<Token> label",
         "Expression : Rc<Expr> expression",
-        "Function   : Token name, Rc<Vec<Token>> params, Rc<Vec<Rc<Stmt>>> body",
+        "ForIn      : Token name, Token keyword, Rc<Expr> iterable, Rc<Stmt> body, Option<Token> label",
+        "Function   : Token name, Vec<Token> modifiers, Rc<Vec<Parameter>> params, Rc<Vec<Rc<Stmt>>> body",
         "If         : Rc<Expr> condition, Rc<Stmt> then_branch, Option<Rc<Stmt>> else_branch",
+        "Loop       : Rc<Stmt> body, Option<Token> label",
+        "Match      : Token keyword, Rc<Expr> subject, Vec<MatchArm<Rc<Stmt>>> arms",
         "Print      : Rc<Expr> expression",
-        // "Return     : Token keyword, Option<Rc<Expr>> value",
-        "Var        : Token name, Option<Rc<Expr>> initializer",
-        "While      : Rc<Expr> condition, Rc<Stmt> body, bool is_for_loop",
+        "Return     : Token keyword, Option<Rc<Expr>> value",
+        "Var        : Token name, Vec<Token> modifiers, Option<Rc<Expr>> initializer",
+        "While      : Rc<Expr> condition, Rc<Stmt> body, Option<Rc<Expr>> increment, Option<Token> label",
     ],
 )?;
 
@@ -103,6 +118,7 @@ fn define_ast(
     }
     writeln!(file, "}}\n")?;
 
+    // every node records the source range it was parsed from
     writeln!(file, "impl {} {{", base_name)?;
     writeln!(file, "    pub fn accept<T>(&self, {}_visitor: &dyn {base_name}Visitor<T>) -> Result<T, LoxError> {{", base_name.to_lowercase())?;
     writeln!(file, "        match self {{")?;
@@ -118,6 +134,14 @@ fn define_ast(
     }
     writeln!(file, "        }}")?;
     writeln!(file, "    }}")?;
+    writeln!(file)?;
+    writeln!(file, "    pub fn span(&self) -> Span {{")?;
+    writeln!(file, "        match self {{")?;
+    for t in &tree_types {
+        writeln!(file, "            {0}::{1}(v) => v.span,", base_name, t.base_class_name)?;
+    }
+    writeln!(file, "        }}")?;
+    writeln!(file, "    }}")?;
     writeln!(file, "}}\n")?;
 
     /*
@@ -129,12 +153,12 @@ fn define_ast(
     writeln!(file, "pub trait {}Visitor<T> {{", base_name)?;
     for t in &tree_types {
         writeln!(file, "    fn visit_{0}_{1}(&self, {1}: &{2}) -> Result<T, LoxError>;", 
-            t.base_class_name.to_lowercase(),
+            snake_case(&t.base_class_name),
             base_name.to_lowercase(),
             t.class_name
-    );
+    )?;
     }
-    writeln!(file, "}}\n");
+    writeln!(file, "}}\n")?;
 
 
     /*
@@ -150,6 +174,7 @@ fn define_ast(
         for f in &t.fields {
             writeln!(file, "    pub {},", f)?;
         }
+        writeln!(file, "    pub span: Span,")?;
         writeln!(file, "}}\n")?;
     }
 
@@ -171,7 +196,7 @@ fn define_ast(
         writeln!(
             file,
             "        visitor.visit_{}_{}(self)",
-            t.base_class_name.to_lowercase(),
+            snake_case(&t.base_class_name),
             base_name.to_lowercase()
         )?;
         writeln!(file, "    }}")?;
@@ -180,3 +205,15 @@ fn define_ast(
     
     Ok(())
 }
+
+// IndexSet -> index_set, so visitor names read like the rest of the code
+fn snake_case(name: &str) -> String {
+    let mut result = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            result.push('_');
+        }
+        result.extend(c.to_lowercase());
+    }
+    result
+}
@@ -0,0 +1,33 @@
+use crate::object::Object;
+use crate::loxcallable::LoxCallable;
+use crate::diagnostic::StackFrame;
+
+use std::rc::Rc;
+use std::fmt;
+
+// how a statement finished; anything but Normal unwinds to the enclosing loop or function
+#[derive(Clone, Debug)]
+pub enum ControlFlow {
+    Normal,
+    // the label of the loop to leave, or None for the innermost one
+    Break(Option<String>),
+    Continue(Option<String>),
+    Return(Object),
+    // 'return f(x)' leaves the call to whoever called this function, so deep tail recursion doesn't grow the stack
+    TailCall(BoundCall),
+}
+
+// a callee with its arguments bound, ready to run
+#[derive(Clone)]
+pub struct BoundCall {
+    pub function: Rc<dyn LoxCallable>,
+    pub arguments: Vec<Option<Object>>,
+    // its call site also places errors raised by natives
+    pub frame: StackFrame,
+}
+
+impl fmt::Debug for BoundCall {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<bound call>")
+    }
+}
@@ -0,0 +1,398 @@
+use crate::span::Span;
+
+use std::fmt::Write;
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const YELLOW: &str = "\x1b[1;33m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+// a piece of source text that spans can point into, by its index in the list
+#[derive(Clone, Debug)]
+pub struct SourceFile {
+    pub name: String,
+    pub text: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(span: Span, message: &str) -> Self {
+        Label {
+            span,
+            message: message.to_string(),
+        }
+    }
+}
+
+// a call in progress: what was called and from where
+#[derive(Clone, Debug)]
+pub struct StackFrame {
+    pub function: String,
+    // the call's closing paren, which errors about the call itself point at too
+    pub call_site: Span,
+    // a native has no source of its own to point into
+    pub native: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiagnosticKind {
+    Scan,
+    Parse,
+    Runtime,
+    Io,
+    Internal,
+    // a warning from the static checks, which doesn't stop the script
+    Lint,
+}
+
+impl DiagnosticKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DiagnosticKind::Scan => "scan",
+            DiagnosticKind::Parse => "parse",
+            DiagnosticKind::Runtime => "runtime",
+            DiagnosticKind::Io => "io",
+            DiagnosticKind::Internal => "internal",
+            DiagnosticKind::Lint => "lint",
+        }
+    }
+
+    pub fn severity(&self) -> &'static str {
+        match self {
+            DiagnosticKind::Lint => "warning",
+            _ => "error",
+        }
+    }
+}
+
+// what went wrong, as a code that tools can match on; lint warnings have codes too
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorCode {
+    UnexpectedCharacter,
+    InvalidNumber,
+    UnterminatedString,
+    UnterminatedInterpolation,
+    InvalidEscape,
+    InvalidUnicodeEscape,
+
+    ExpectedToken,
+    InvalidAssignmentTarget,
+    TooManyArguments,
+    DuplicateParameter,
+    MisplacedParameter,
+    DuplicateLabel,
+    UndefinedLabel,
+    AlreadyDeclared,
+    InvalidModifier,
+    InvalidOverride,
+    MisplacedJump,
+    MisplacedReturn,
+    MisplacedThisOrSuper,
+    InheritsFromItself,
+    ReadInOwnInitializer,
+    NestedTooDeeply,
+
+    UndefinedVariable,
+    UndefinedProperty,
+    UndefinedMapKey,
+    ArityMismatch,
+    InvalidNamedArgument,
+    NotCallable,
+    InvalidOperand,
+    InvalidIndex,
+    IndexOutOfBounds,
+    NotIndexable,
+    NotAnInstance,
+    InvalidSuperclass,
+    NotIterable,
+    InvalidMapKey,
+    NoMatchingArm,
+    StackOverflow,
+    NotDynamic,
+    PrivateMember,
+    InvalidArgument,
+    ClockUnavailable,
+
+    UnreadableFile,
+
+    Internal,
+
+    NonExhaustiveMatch,
+}
+
+impl ErrorCode {
+    // codes are part of the json schema, so they are never reused for another error
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorCode::UnexpectedCharacter => "E0101",
+            ErrorCode::InvalidNumber => "E0102",
+            ErrorCode::UnterminatedString => "E0103",
+            ErrorCode::UnterminatedInterpolation => "E0104",
+            ErrorCode::InvalidEscape => "E0105",
+            ErrorCode::InvalidUnicodeEscape => "E0106",
+
+            ErrorCode::ExpectedToken => "E0201",
+            ErrorCode::InvalidAssignmentTarget => "E0202",
+            ErrorCode::TooManyArguments => "E0203",
+            ErrorCode::DuplicateParameter => "E0204",
+            ErrorCode::MisplacedParameter => "E0205",
+            ErrorCode::DuplicateLabel => "E0206",
+            ErrorCode::UndefinedLabel => "E0207",
+            ErrorCode::AlreadyDeclared => "E0208",
+            ErrorCode::InvalidModifier => "E0209",
+            ErrorCode::InvalidOverride => "E0210",
+            ErrorCode::MisplacedJump => "E0211",
+            ErrorCode::MisplacedReturn => "E0212",
+            ErrorCode::MisplacedThisOrSuper => "E0213",
+            ErrorCode::InheritsFromItself => "E0214",
+            ErrorCode::ReadInOwnInitializer => "E0215",
+            ErrorCode::NestedTooDeeply => "E0216",
+
+            ErrorCode::UndefinedVariable => "E0301",
+            ErrorCode::UndefinedProperty => "E0302",
+            ErrorCode::UndefinedMapKey => "E0303",
+            ErrorCode::ArityMismatch => "E0304",
+            ErrorCode::InvalidNamedArgument => "E0305",
+            ErrorCode::NotCallable => "E0306",
+            ErrorCode::InvalidOperand => "E0307",
+            ErrorCode::InvalidIndex => "E0308",
+            ErrorCode::IndexOutOfBounds => "E0309",
+            ErrorCode::NotIndexable => "E0310",
+            ErrorCode::NotAnInstance => "E0311",
+            ErrorCode::InvalidSuperclass => "E0312",
+            ErrorCode::NotIterable => "E0313",
+            ErrorCode::InvalidMapKey => "E0314",
+            ErrorCode::NoMatchingArm => "E0315",
+            ErrorCode::StackOverflow => "E0316",
+            ErrorCode::NotDynamic => "E0317",
+            ErrorCode::PrivateMember => "E0318",
+            ErrorCode::InvalidArgument => "E0319",
+            ErrorCode::ClockUnavailable => "E0320",
+
+            ErrorCode::UnreadableFile => "E0401",
+
+            ErrorCode::Internal => "E0501",
+
+            ErrorCode::NonExhaustiveMatch => "W0101",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub code: ErrorCode,
+    pub message: String,
+    // errors that don't come from a place in the source have no primary label
+    pub primary: Option<Label>,
+    pub secondary: Vec<Label>,
+    // the calls in progress when a runtime error happened, outermost first
+    pub trace: Vec<StackFrame>,
+}
+
+impl Diagnostic {
+    pub fn new(kind: DiagnosticKind, code: ErrorCode, message: &str, primary: Option<Label>) -> Self {
+        Diagnostic {
+            kind,
+            code,
+            message: message.to_string(),
+            primary,
+            secondary: Vec::new(),
+            trace: Vec::new(),
+        }
+    }
+}
+
+pub struct Renderer<'a> {
+    sources: &'a [SourceFile],
+    format: ErrorFormat,
+    color: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(sources: &'a [SourceFile], format: ErrorFormat, color: bool) -> Self {
+        Renderer { sources, format, color }
+    }
+
+    pub fn emit(&self, diagnostics: &[Diagnostic]) {
+        for diagnostic in diagnostics {
+            match self.format {
+                ErrorFormat::Human => eprint!("{}", self.render(diagnostic)),
+                ErrorFormat::Json => eprintln!("{}", self.render_json(diagnostic)),
+            }
+        }
+    }
+
+    // one object per line, following diagnostic.schema.json
+    pub fn render_json(&self, diagnostic: &Diagnostic) -> String {
+        let mut out = String::new();
+        let _ = write!(out, "{{\"kind\":{},\"severity\":{},\"code\":{},\"message\":{},", json_string(diagnostic.kind.name()), json_string(diagnostic.kind.severity()), json_string(diagnostic.code.code()), json_string(&diagnostic.message));
+        match &diagnostic.primary {
+            Some(primary) => out.push_str(&self.json_location(primary.span)),
+            None => out.push_str("\"file\":null,\"line\":null,\"column\":null,\"span\":null"),
+        }
+
+        let labels: Vec<String> = diagnostic.secondary.iter()
+            .map(|label| format!("{{\"message\":{},{}}}", json_string(&label.message), self.json_location(label.span)))
+            .collect();
+        let _ = write!(out, ",\"labels\":[{}]", labels.join(","));
+
+        let trace: Vec<String> = backtrace(diagnostic).iter()
+            .map(|(function, span)| match span {
+                Some(span) => format!("{{\"function\":{},{}}}", json_string(function), self.json_location(*span)),
+                None => format!("{{\"function\":{},\"file\":null,\"line\":null,\"column\":null,\"span\":null}}", json_string(function)),
+            })
+            .collect();
+        let _ = write!(out, ",\"trace\":[{}]}}", trace.join(","));
+        out
+    }
+
+    fn json_location(&self, span: Span) -> String {
+        let name = self.sources.get(span.file).map(|source| source.name.as_str()).unwrap_or("<unknown>");
+        format!("\"file\":{},\"line\":{},\"column\":{},\"span\":{{\"start\":{},\"end\":{}}}", json_string(name), span.line, span.column, span.start, span.end)
+    }
+
+    // error: message (or warning: message)
+    //  --> file:line:column
+    //   |
+    // 3 | print a + ;
+    //   |           ^ primary label
+    // backtrace:
+    //   at inner (file:line:column)
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut out = self.render_source(diagnostic);
+
+        let trace = backtrace(diagnostic);
+        if !trace.is_empty() {
+            let _ = writeln!(out, "backtrace:");
+        }
+        // deep recursion would repeat the same line for every call
+        let mut index = 0;
+        while index < trace.len() {
+            let repeats = trace[index..].iter().take_while(|line| **line == trace[index]).count();
+            let (function, span) = &trace[index];
+            let location = match span {
+                Some(span) => self.location(*span),
+                None => String::from("native"),
+            };
+            let _ = writeln!(out, "  at {function} ({location})");
+            if repeats > 1 {
+                let _ = writeln!(out, "  ... the line above repeated {} more times", repeats - 1);
+            }
+            index += repeats;
+        }
+        out
+    }
+
+    fn render_source(&self, diagnostic: &Diagnostic) -> String {
+        let mut out = String::new();
+        let severity = diagnostic.kind.severity();
+        let accent = if severity == "warning" { YELLOW } else { RED };
+        let _ = writeln!(out, "{}: {}", self.paint(severity, accent), self.paint(&diagnostic.message, BOLD));
+
+        let primary = match &diagnostic.primary {
+            Some(primary) => primary,
+            None => return out,
+        };
+
+        // labels are shown in source order, the primary label's file first
+        let mut labels: Vec<(&Label, bool)> = vec![(primary, true)];
+        labels.extend(diagnostic.secondary.iter().map(|label| (label, false)));
+        labels.sort_by_key(|(label, _)| (label.span.file != primary.span.file, label.span.file, label.span.line, label.span.column));
+
+        let width = labels.iter().map(|(label, _)| label.span.line.to_string().len()).max().unwrap_or(1);
+        let gutter = self.paint("|", BLUE);
+
+        let _ = writeln!(out, "{:width$}{} {}", "", self.paint("-->", BLUE), self.location(primary.span));
+        let _ = writeln!(out, "{:width$} {}", "", gutter);
+
+        let mut shown: Option<(usize, usize)> = None;
+        for (label, is_primary) in labels {
+            let span = label.span;
+            if shown.is_some_and(|(file, _)| file != span.file) {
+                let _ = writeln!(out, "{:width$}{} {}", "", self.paint(":::", BLUE), self.location(span));
+                let _ = writeln!(out, "{:width$} {}", "", gutter);
+            }
+
+            let text = self.sources.get(span.file).map(|source| source.text.as_str()).unwrap_or("");
+            if shown.is_some_and(|(file, line)| file == span.file && span.line > line + 1) {
+                let _ = writeln!(out, "{}", self.paint("...", BLUE));
+            }
+            if shown != Some((span.file, span.line)) {
+                let line = text.lines().nth(span.line.saturating_sub(1)).unwrap_or("");
+                let _ = writeln!(out, "{} {} {}", self.paint(&format!("{:>width$}", span.line), BLUE), gutter, line);
+                shown = Some((span.file, span.line));
+            }
+
+            // a span running past the end of its line is underlined up to the line end
+            let covered = text.get(span.start..span.end.min(text.len())).unwrap_or("");
+            let length = covered.split('\n').next().unwrap_or("").chars().count().max(1);
+            let (marker, style) = if is_primary { ("^", accent) } else { ("-", BLUE) };
+            let underline = format!("{} {}", marker.repeat(length), label.message);
+            let _ = writeln!(out, "{:width$} {} {}{}", "", gutter, " ".repeat(span.column.saturating_sub(1)), self.paint(underline.trim_end(), style));
+        }
+
+        out
+    }
+
+    fn location(&self, span: Span) -> String {
+        let name = self.sources.get(span.file).map(|source| source.name.as_str()).unwrap_or("<unknown>");
+        format!("{}:{}:{}", name, span.line, span.column)
+    }
+
+    fn paint(&self, text: &str, style: &str) -> String {
+        if self.color {
+            format!("{style}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+// innermost call first, each at the place it had got to: the error itself for the innermost
+// and the call into the next frame in for the rest
+fn backtrace(diagnostic: &Diagnostic) -> Vec<(String, Option<Span>)> {
+    if diagnostic.trace.is_empty() {
+        return Vec::new();
+    }
+
+    let mut lines = Vec::new();
+    let mut position = diagnostic.primary.as_ref().map(|label| label.span);
+    for frame in diagnostic.trace.iter().rev() {
+        lines.push((frame.function.clone(), if frame.native { None } else { position }));
+        position = Some(frame.call_site);
+    }
+    lines.push((String::from("<script>"), position));
+    lines
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
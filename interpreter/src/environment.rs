@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use crate::object::Object;
 use crate::token::Token;
 use crate::error::LoxError;
+use crate::diagnostic::ErrorCode;
 use std::rc::Rc;
 use std::cell::RefCell;
 
@@ -37,25 +38,48 @@ impl Environment {
         if let Some(object) = self.values.get(&name.lexeme) {
             return Ok(object.clone());
         } 
-        else if self.enclosing.clone() != None {
-            return Ok(self.enclosing.as_ref().unwrap().borrow().get(name)?);
+        else if let Some(enclosing) = &self.enclosing {
+            return Ok(enclosing.borrow().get(name)?);
         }
         else {
-            return Err(LoxError::runtime_error(&name, String::from(format!("Undefined variable '{}'.", name.lexeme))));
+            return Err(LoxError::runtime_error(name, ErrorCode::UndefinedVariable, String::from(format!("Undefined variable '{}'.", name.lexeme))));
         }                                           
     }
     
+    pub fn get_at(&self, distance: usize, name: &Token) -> Result<Object, LoxError> {
+        if distance == 0 {
+            if let Some(object) = self.values.get(&name.lexeme) {
+                return Ok(object.clone());
+            }
+        } 
+        else if let Some(enclosing) = &self.enclosing {
+            return enclosing.borrow().get_at(distance - 1, name);
+        }
+        Err(LoxError::runtime_error(name, ErrorCode::UndefinedVariable, String::from(format!("Undefined variable '{}'.", name.lexeme))))
+    }
+
+    pub fn assign_at(&mut self, distance: usize, name: &Token, value: Object) -> Result<(), LoxError> {
+        if distance == 0 {
+            self.values.insert(name.lexeme.clone(), value);
+            return Ok(());
+        }
+        if let Some(enclosing) = &self.enclosing {
+            return enclosing.borrow_mut().assign_at(distance - 1, name, value);
+        }
+        Err(LoxError::runtime_error(name, ErrorCode::UndefinedVariable, String::from(format!("Undefined variable '{}'.", name.lexeme))))
+    }
+
     pub fn assign(&mut self, name: &Token, value: Object) -> Result<(), LoxError>{
         if self.values.contains_key(&name.lexeme) {
             self.values.insert(name.lexeme.clone(), value.clone());
             return Ok(());
         }
-        if self.enclosing != None {
-            self.enclosing.as_ref().unwrap().borrow_mut().assign(name, value)?;
+        if let Some(enclosing) = &self.enclosing {
+            enclosing.borrow_mut().assign(name, value)?;
             return Ok(());
         } 
         else {
-            Err(LoxError::runtime_error(name, String::from(format!("Undefined variable '{}'.", &name.lexeme))))
+            Err(LoxError::runtime_error(name, ErrorCode::UndefinedVariable, String::from(format!("Undefined variable '{}'.", &name.lexeme))))
         }
         
     }
@@ -1,93 +1,132 @@
 use crate::token::Token;
 use crate::tokentype::TokenType::*;
-// use crate::object::Object;
+use crate::span::Span;
+use crate::diagnostic::*;
 // use crate::expr::Expr;
 
 #[derive(Clone, Debug)]
 pub enum LoxError {
-    Error { line: usize, message: String },
-    ParseError {token: Token, message: String},
-    RuntimeError {operator: Token, message: String},
-    BreakError,
-    ContinueError,
-    Null
+    ScanError { span: Span, code: ErrorCode, message: String },
+    ParseError {token: Token, code: ErrorCode, message: String},
+    RuntimeError {operator: Token, code: ErrorCode, message: String, labels: Vec<Label>, trace: Vec<StackFrame>},
+    // the span is filled in at the call site, natives don't know where they were called from
+    NativeError {name: String, code: ErrorCode, message: String, span: Option<Span>, trace: Vec<StackFrame>},
+    // a bug in the interpreter rather than in the script
+    InternalError {message: String, span: Option<Span>},
+    IoError {message: String},
+    // every scan, parse and resolve error of a script that can't be run
+    ParseFailure {errors: Vec<LoxError>},
 }
 
 impl LoxError {
-    pub fn break_error() -> LoxError{
-        LoxError::BreakError
+    pub fn native_error(name: &str, code: ErrorCode, message: String) -> LoxError{
+        LoxError::NativeError { name: name.to_string(), code, message, span: None, trace: Vec::new() }
     }
-    pub fn continue_error() -> LoxError{
-        LoxError::ContinueError
+    pub fn internal_error(message: String, span: Option<Span>) -> LoxError{
+        LoxError::InternalError { message, span }
     }
-    pub fn error(line: usize, message: String) -> LoxError{
-        let mut err = LoxError::Error {
-            line, message
-        };
-        err.report(String::from(""));
-        //Lox.had_error = true;
-        err
+    pub fn io_error(message: String) -> LoxError{
+        LoxError::IoError { message }
+    }
+
+    pub fn scan_error(span: Span, code: ErrorCode, message: String) -> LoxError {
+        LoxError::ScanError {
+            span, code, message
+        }
     }
 
-    pub fn parse_error(token: &Token, message: String) -> LoxError {
-        let mut err = LoxError::ParseError {
+    pub fn parse_error(token: &Token, code: ErrorCode, message: String) -> LoxError {
+        LoxError::ParseError {
             token: token.clone(),
+            code,
             message: message
-        };
+        }
+    }
 
-        err.report(String::from(""));
+    pub fn runtime_error(operator: &Token, code: ErrorCode, message: String) -> LoxError {
+        LoxError::RuntimeError {
+            operator: operator.clone(), code, message: message, labels: Vec::new(), trace: Vec::new()
+        }
+    }
 
-        err
+    pub fn parse_failure(errors: Vec<LoxError>) -> LoxError {
+        LoxError::ParseFailure { errors }
     }
 
-    pub fn runtime_error(operator: &Token, message: String) -> LoxError {
-        let mut err = LoxError::RuntimeError {
-            operator: operator.clone(),message: message
-        };
+    // points a runtime error at a second place, e.g. the declaration of a function called wrongly
+    pub fn with_label(mut self, label: Label) -> LoxError {
+        if let LoxError::RuntimeError {labels, ..} = &mut self {
+            labels.push(label);
+        }
+        self
+    }
 
-        err.report(String::from(""));
+    // the trace is taken where the error happened, so the first one given is kept
+    pub fn with_trace(mut self, stack: &[StackFrame]) -> LoxError {
+        if let LoxError::RuntimeError {trace, ..} | LoxError::NativeError {trace, ..} = &mut self {
+            if trace.is_empty() {
+                *trace = stack.to_vec();
+            }
+        }
+        self
+    }
 
-        err
+    // the calls in progress when a runtime error happened, outermost first
+    pub fn trace(&self) -> &[StackFrame] {
+        match self {
+            LoxError::RuntimeError {trace, ..} | LoxError::NativeError {trace, ..} => trace,
+            _ => &[]
+        }
     }
-    
-    pub fn null() -> LoxError {
-        // error pretty much already handled ?
-        LoxError::Null
+
+    // where the error points, if anywhere
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            LoxError::ScanError {span, ..} => Some(*span),
+            LoxError::ParseError {token, ..} => Some(token.span),
+            LoxError::RuntimeError {operator, ..} => Some(operator.span),
+            LoxError::NativeError {span, ..} | LoxError::InternalError {span, ..} => *span,
+            LoxError::IoError {..} | LoxError::ParseFailure {..} => None,
+        }
     }
-    
 
-    pub fn report(&mut self, err: String) {
+    // a parse failure stands for all of its errors
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
         match self {
-            LoxError::Error {line, message} => {
-                //self.print_error(*line, err, *message);
-                eprintln!("[line {0}] Error{1}: {2}", line, err, message);
+            LoxError::ParseFailure {errors} => errors.iter().flat_map(|e| e.diagnostics()).collect(),
+            _ => self.diagnostic().into_iter().collect(),
+        }
+    }
+
+    // a parse failure is shown through the errors it collects
+    fn diagnostic(&self) -> Option<Diagnostic> {
+        let mut diagnostic = match self {
+            LoxError::ScanError {span, code, message} => {
+                Diagnostic::new(DiagnosticKind::Scan, *code, message, Some(Label::new(*span, "")))
             }
-            LoxError::ParseError {token, message} => {
-                if token.t_type == EOF {
-                    //self.print_error(token.line, String::from(" at end"), message.to_string())
-                    eprintln!("[line {0}] Error{1}: {2}", token.line, " at end", message);
-                } else {
-                    //self.print_error(token.line, String::from(" at '") + &token.lexeme + &String::from("'"), message.to_string())
-                    eprintln!("[line {0}] Error{1}: {2}", token.line, String::from(" at '") + &token.lexeme + &String::from("'"), message);
-                }
+            LoxError::ParseError {token, code, message} => {
+                let label = if token.t_type == EOF { "at end" } else { "" };
+                Diagnostic::new(DiagnosticKind::Parse, *code, message, Some(Label::new(token.span, label)))
             }
-            LoxError::RuntimeError {operator, message} => {
-                eprintln!("{} [line {}]", message, operator.line)
+            LoxError::RuntimeError {operator, code, message, labels, ..} => {
+                let mut diagnostic = Diagnostic::new(DiagnosticKind::Runtime, *code, message, Some(Label::new(operator.span, "")));
+                diagnostic.secondary = labels.clone();
+                diagnostic
             }
-            LoxError::ContinueError => {
+            LoxError::NativeError {name, code, message, span, ..} => {
+                let message = format!("{name}(): {message}");
+                Diagnostic::new(DiagnosticKind::Runtime, *code, &message, span.map(|span| Label::new(span, "")))
             }
-            LoxError::BreakError => {
+            LoxError::InternalError {message, span} => {
+                let message = format!("Internal interpreter error: {message}");
+                Diagnostic::new(DiagnosticKind::Internal, ErrorCode::Internal, &message, span.map(|span| Label::new(span, "")))
             }
-            LoxError::Null => {
-                panic!("IDK bro")
+            LoxError::IoError {message} => {
+                Diagnostic::new(DiagnosticKind::Io, ErrorCode::UnreadableFile, message, None)
             }
-        }
-
+            LoxError::ParseFailure {..} => return None
+        };
+        diagnostic.trace = self.trace().to_vec();
+        Some(diagnostic)
     }
-
-    // fn print_error(&mut self, line: usize, whr: String, message: String) {
-    //     eprintln!("[line {0}] Error{1}: {2}", line, whr, message);
-    // }
 }
-
-    
\ No newline at end of file
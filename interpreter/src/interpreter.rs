@@ -3,23 +3,46 @@
 use crate::expr::*;
 use crate::object::Object;
 use crate::error::LoxError;
+use crate::controlflow::{ControlFlow, BoundCall};
 use crate::tokentype::TokenType::*;
 use crate::token::Token;
 use crate::stmt::*;
 use crate::environment::Environment;
 use crate::nativefunction;
 use crate::loxfunction::*;
+use crate::loxcallable::LoxCallable;
+use crate::span::Span;
+use crate::diagnostic::{StackFrame, ErrorCode};
+use crate::loxclass::*;
+use crate::loxinstance::LoxInstance;
+use crate::loxmap::*;
+use crate::loxarray::*;
+use crate::pattern::*;
+use crate::loxiterator::LoxIterator;
 
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::iter::Iterator;
 
 
 
+// deep enough for real recursion, shallow enough to stop before the native stack runs out
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+// native stack a call needs to be sure of finishing, with its body nested as deeply as the parser allows
+const STACK_PER_CALL_RESERVE: usize = 4 * 1024 * 1024;
+
 #[derive(Clone, Debug)]
 pub struct Interpreter {
     pub globals: Rc<RefCell<Environment>>,
-    environment: RefCell<Rc<RefCell<Environment>>>
+    environment: RefCell<Rc<RefCell<Environment>>>,
+    // scope distance of each resolved local, keyed by the address of its expr node
+    locals: RefCell<HashMap<usize, usize>>,
+    // calls in progress, innermost last; a tail call takes over its caller's frame
+    call_stack: RefCell<Vec<StackFrame>>,
+    pub max_call_depth: usize,
+    // where the native stack starts and how big it is, when the thread was made for the interpreter
+    stack: Option<(usize, usize)>
 }
 
 impl Interpreter {
@@ -29,22 +52,60 @@ impl Interpreter {
         globals.borrow_mut().define(
             "clock", 
             Object::Native(Rc::new(nativefunction::LoxNative{
-                func: Rc::new(NativeClock{})
+                func: Rc::new(nativefunction::NativeClock{})
             })));
+        globals.borrow_mut().define("len", Object::Native(Rc::new(nativefunction::LoxNative{func: Rc::new(nativefunction::NativeLen{})})));
+        globals.borrow_mut().define("keys", Object::Native(Rc::new(nativefunction::LoxNative{func: Rc::new(nativefunction::NativeKeys{})})));
+        globals.borrow_mut().define("has", Object::Native(Rc::new(nativefunction::LoxNative{func: Rc::new(nativefunction::NativeHas{})})));
+        globals.borrow_mut().define("remove", Object::Native(Rc::new(nativefunction::LoxNative{func: Rc::new(nativefunction::NativeRemove{})})));
         Interpreter {
             globals: Rc::clone(&globals),
             environment: RefCell::new(Rc::clone(&globals)),   
+            locals: RefCell::new(HashMap::new()),
+            call_stack: RefCell::new(Vec::new()),
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            stack: None
+        }
+    }
+
+    // measured from the caller's frame, so call it near the top of the interpreter's thread
+    pub fn set_stack_size(&mut self, size: usize) {
+        self.stack = Some((stack_address(), size));
+    }
+
+    fn out_of_stack(&self) -> bool {
+        match self.stack {
+            Some((base, size)) => base.abs_diff(stack_address()) + STACK_PER_CALL_RESERVE > size,
+            None => false
+        }
+    }
+
+    pub fn expr_key<T>(expr: &T) -> usize {
+        expr as *const T as usize
+    }
+
+    pub fn resolve(&self, key: usize, depth: usize) {
+        self.locals.borrow_mut().insert(key, depth);
+    }
+
+    pub fn resolve_global(&self, key: usize) {
+        // a previous program's node may have lived at the same address
+        self.locals.borrow_mut().remove(&key);
+    }
+
+    fn look_up_variable(&self, name: &Token, key: usize) -> Result<Object, LoxError> {
+        match self.locals.borrow().get(&key) {
+            Some(distance) => self.environment.borrow().borrow().get_at(*distance, name),
+            None => self.globals.borrow().get(name)
         }
     }
     pub fn interpret(&self, statements: Vec<Rc<Stmt>>) -> Result<(), LoxError> {
         for statement in statements {
-            self.execute(statement)?;
-            // match self.execute(&statement)? {
-            //     LoxError => { 
-            //         return Err(LoxError::null()); //TODO: fix error handling
-            //     }
-            //     _=> {return Ok(());}
-            // }
+            // the resolver rejects break, continue and return outside of a loop or function
+            if let ControlFlow::Normal = self.execute(statement.clone())? {
+                continue;
+            }
+            return Err(LoxError::internal_error(String::from("Control flow escaped to the top level."), Some(statement.span())));
         }
         Ok(())
     }
@@ -53,35 +114,181 @@ impl Interpreter {
         return expr.clone().accept(self)
     }
 
-    fn execute(&self, stmt: Rc<Stmt>) -> Result<(), LoxError>{
-        stmt.accept(self)?;
-        Ok(())
+    fn execute(&self, stmt: Rc<Stmt>) -> Result<ControlFlow, LoxError>{
+        stmt.accept(self)
     }
 
     pub fn execute_block(&self, statements: &Rc<Vec<Rc<Stmt>>>, 
-        environment: Environment // environment: RefCell<Rc<RefCell<Environment>>>
-    ) -> Result<(), LoxError> {
+        environment: Rc<RefCell<Environment>>
+    ) -> Result<ControlFlow, LoxError> {
+        let previous = self.environment.replace(environment);
+
+        let mut result = Ok(ControlFlow::Normal);
+        for statement in statements.iter() {
+            result = self.execute(statement.clone());
+            if !matches!(result, Ok(ControlFlow::Normal)) {
+                break;
+            }
+        }
+
+        self.environment.replace(previous);
+
+        result
+    }
+
+    // the callee with its arguments bound to its signature
+    fn bind_call(&self, expr: &CallExpr) -> Result<BoundCall, LoxError> {
+        let callee = self.evaluate(expr.callee.clone())?;
+
+        let mut arguments = Vec::new();
+
+        for argument in expr.arguments.clone() {
+            arguments.push(self.evaluate(argument)?);
+        }
+
+        let mut named = Vec::new();
+        for argument in expr.named.iter() {
+            named.push((argument.name.clone(), self.evaluate(argument.value.clone())?));
+        }
+        
+        let native = matches!(callee, Object::Native(_));
+        let function: Rc<dyn LoxCallable> = match callee {
+            Object::Func(function) => function,
+            Object::Native(native) => Rc::clone(&native.func),
+            Object::Class(class) => Rc::new(class),
+            _ => {
+                return Err(LoxError::runtime_error(&expr.paren, ErrorCode::NotCallable, String::from("Can only call functions or classes")))
+            }
+        };
+
+        match function.signature().bind(arguments, named, &expr.paren) {
+            Ok(arguments) => {
+                let frame = StackFrame{function: function.name(), call_site: expr.paren.span, native};
+                Ok(BoundCall{function, arguments, frame})
+            }
+            Err(error) => match function.declaration() {
+                Some(label) => Err(error.with_label(label)),
+                None => Err(error)
+            }
+        }
+    }
+
+    // runs a call in a new frame; errors leaving it carry the stack as it was where they happened
+    pub fn call(&self, call: BoundCall, token: &Token) -> Result<Object, LoxError> {
+        if self.call_stack.borrow().len() >= self.max_call_depth {
+            return Err(LoxError::runtime_error(token, ErrorCode::StackOverflow, String::from(format!("Stack overflow: more than {} nested calls.", self.max_call_depth))));
+        }
+        if self.out_of_stack() {
+            return Err(LoxError::runtime_error(token, ErrorCode::StackOverflow, format!("Stack overflow: out of native stack after {} nested calls.", self.call_stack.borrow().len())));
+        }
+
+        self.call_stack.borrow_mut().push(call.frame.clone());
+        let result = call.function.call_frame(self, call.arguments)
+            .map_err(|error| at_call_site(error, call.frame.call_site))
+            .and_then(|flow| self.run_tail_calls(flow))
+            .map_err(|error| error.with_trace(&self.call_stack.borrow()));
+        self.call_stack.borrow_mut().pop();
+        result
+    }
+
+    // the trampoline: each tail call runs here once the frame that made it has returned
+    pub fn run_tail_calls(&self, mut flow: ControlFlow) -> Result<Object, LoxError> {
+        loop {
+            flow = match flow {
+                ControlFlow::TailCall(call) => {
+                    // the caller's frame is reused, so it is still entered from the same call site
+                    if let Some(top) = self.call_stack.borrow_mut().last_mut() {
+                        top.function = call.frame.function.clone();
+                        top.native = call.frame.native;
+                    }
+                    call.function.call_frame(self, call.arguments).map_err(|error| at_call_site(error, call.frame.call_site))?
+                }
+                ControlFlow::Return(value) => return Ok(value),
+                _ => return Err(LoxError::internal_error(String::from("A call finished without returning."), None))
+            };
+        }
+    }
+
+    pub fn evaluate_in(&self, expr: Rc<Expr>, environment: Rc<RefCell<Environment>>) -> Result<Object, LoxError> {
+        let previous = self.environment.replace(environment);
+
+        let result = self.evaluate(expr);
+
+        self.environment.replace(previous);
+
+        result
+    }
+
+    pub fn execute_in(&self, stmt: Rc<Stmt>, environment: Environment) -> Result<ControlFlow, LoxError> {
         let previous = self.environment.replace(Rc::new(RefCell::new(environment)));
 
-        let result = statements.iter().try_for_each(|statement| self.execute(statement.clone()));
+        let result = self.execute(stmt);
 
         self.environment.replace(previous);
 
         result
     }
 
-    fn is_truthy(&self, object: &Object) -> bool {
+    // evaluated with the class environment active, since static fields are initialized here
+    fn class_members(&self, stmt: &ClassStmt, superclass: &Option<Rc<LoxClass>>) -> Result<ClassMembers, LoxError> {
+        let mut members = ClassMembers::default();
+        for method in stmt.methods.iter() {
+            if has_modifier(&method.modifiers, OVERRIDE) {
+                let overridden = superclass.as_ref().and_then(|superclass| superclass.find_method(&method.name.lexeme));
+                if overridden.is_none() {
+                    return Err(LoxError::runtime_error(&method.name, ErrorCode::InvalidOverride, String::from(format!("Method '{}' is marked 'override' but no superclass method has that name.", method.name.lexeme))))
+                }
+            }
+            if has_modifier(&method.modifiers, PRIVATE) {
+                members.private.insert(method.name.lexeme.clone());
+            }
+
+            let function = Rc::new(LoxFunction::new(method, &self.environment.borrow(), method.name.lexeme == "init"));
+            if has_modifier(&method.modifiers, STATIC) {
+                members.static_methods.insert(method.name.lexeme.clone(), function);
+            } else {
+                members.methods.insert(method.name.lexeme.clone(), function);
+            }
+        }
+
+        for field in stmt.fields.iter() {
+            if has_modifier(&field.modifiers, PRIVATE) {
+                members.private.insert(field.name.lexeme.clone());
+            }
+
+            if has_modifier(&field.modifiers, STATIC) {
+                let value = match field.initializer.clone() {
+                    Some(initializer) => self.evaluate(initializer)?,
+                    None => Object::Nil
+                };
+                members.statics.insert(field.name.lexeme.clone(), value);
+            } else {
+                members.fields.push(Rc::clone(field));
+            }
+        }
+        Ok(members)
+    }
+
+    pub fn is_truthy(&self, object: &Object) -> bool {
         if *object == Object::Nil {
             return false;
         } 
-        match object {
-            Object::Bool(b) => {
-                return *b;
-            }
-            _ => {}
+        if let Object::Bool(b) = object {
+            return *b;
         }
         return true;
     }
+    fn match_arm<'a, T>(&self, value: &Object, arms: &'a [MatchArm<T>]) -> Option<&'a MatchArm<T>> {
+        arms.iter().find(|arm| arm.patterns.iter().any(|pattern| match pattern {
+            Pattern::Literal(literal) => self.is_equal(value.clone(), literal.clone()),
+            Pattern::Range(start, end, inclusive) => match value {
+                Object::Number(n) => start <= n && (n < end || *inclusive && n == end),
+                _ => false
+            }
+            Pattern::Wildcard => true,
+        }))
+    }
+
     fn is_equal(&self, a: Object, b: Object) -> bool {
         if a == Object::Nil && b == Object::Nil {return true;}
         if a == Object::Nil {return false;}
@@ -90,13 +297,51 @@ impl Interpreter {
             (Object::Bool(a), Object::Bool(b)) => return a == b,
             (Object::String(a), Object::String(b)) => return a == b,
             (Object::Number(a), Object::Number(b)) => return a == b,
+            (Object::Class(a), Object::Class(b)) => return Rc::ptr_eq(&a, &b),
+            (Object::Instance(a), Object::Instance(b)) => return Rc::ptr_eq(&a, &b),
+            (Object::Array(a), Object::Array(b)) => return Rc::ptr_eq(&a, &b),
+            (Object::Map(a), Object::Map(b)) => return Rc::ptr_eq(&a, &b),
             _ => return false,
         }
     }
+    fn map_key(&self, token: &Token, key: &Object) -> Result<MapKey, LoxError> {
+        MapKey::from_object(key).map_err(|message| LoxError::runtime_error(token, ErrorCode::InvalidMapKey, message))
+    }
+
+    fn map_get(&self, token: &Token, map: &LoxMap, key: &MapKey) -> Result<Object, LoxError> {
+        match map.get(key) {
+            Some(value) => Ok(value),
+            None => Err(LoxError::runtime_error(token, ErrorCode::UndefinedMapKey, String::from(format!("Undefined map key {key}."))))
+        }
+    }
+
+    // the operands were already checked to be numbers, so this can't happen
+    fn unchecked_operands(&self, expr: &BinaryExpr) -> LoxError {
+        LoxError::internal_error(format!("Operands of '{}' passed the number check but aren't numbers.", expr.operator.lexeme), Some(expr.span))
+    }
+
+    fn array_index(&self, bracket: &Token, array: &[Object], index: &Object) -> Result<usize, LoxError> {
+        let index = match index {
+            Object::Number(n) => *n,
+            _ => return Err(LoxError::runtime_error(bracket, ErrorCode::InvalidIndex, String::from("Array index must be a number.")))
+        };
+
+        if index.fract() != 0.0 {
+            return Err(LoxError::runtime_error(bracket, ErrorCode::InvalidIndex, String::from(format!("Array index must be an integer, got {}.", index))));
+        }
+        if index < 0.0 {
+            return Err(LoxError::runtime_error(bracket, ErrorCode::InvalidIndex, String::from(format!("Array index can't be negative, got {}.", index))));
+        }
+        if index >= array.len() as f64 {
+            return Err(LoxError::runtime_error(bracket, ErrorCode::IndexOutOfBounds, String::from(format!("Array index {} out of bounds for length {}.", index, array.len()))));
+        }
+        Ok(index as usize)
+    }
+
     fn check_number_operand(&self, operator: Token, operand: &Object) -> Result<(), LoxError> {
         match operand {
             Object::Number(_) => {Ok(())}
-            _ => {Err(LoxError::runtime_error(&operator, String::from("Operand must be a number.")))} 
+            _ => {Err(LoxError::runtime_error(&operator, ErrorCode::InvalidOperand, String::from("Operand must be a number.")))} 
         }
     }
     fn check_number_operands(&self, operator: Token, left: &Object, right: &Object) -> Result<(), LoxError> {
@@ -104,59 +349,171 @@ impl Interpreter {
             (Object::Number(_), Object::Number(_)) => {
                 Ok(())
             }
-            _=> Err(LoxError::runtime_error(&operator, String::from("Operands must be numbers.")))
+            _=> Err(LoxError::runtime_error(&operator, ErrorCode::InvalidOperand, String::from("Operands must be numbers.")))
         }
     }
 }
 
-impl StmtVisitor<()> for Interpreter {
+impl StmtVisitor<ControlFlow> for Interpreter {
 
-    fn visit_expression_stmt(&self, stmt: &ExpressionStmt) -> Result<(), LoxError> {
+    fn visit_expression_stmt(&self, stmt: &ExpressionStmt) -> Result<ControlFlow, LoxError> {
         self.evaluate(stmt.expression.clone())?;
-        return Ok(());
+        return Ok(ControlFlow::Normal);
+    }
+
+    fn visit_for_in_stmt(&self, stmt: &ForInStmt) -> Result<ControlFlow, LoxError> {
+        let iterable = self.evaluate(stmt.iterable.clone())?;
+        let mut iterator = LoxIterator::new(iterable, self, &stmt.keyword)?;
+
+        while let Some(value) = iterator.next(self, &stmt.keyword)? {
+            // a fresh binding each time round, so closures made in the body keep their own value
+            let mut environment = Environment::new_enclosing(self.environment.borrow().clone());
+            environment.define(&stmt.name.lexeme, value);
+
+            match self.execute_in(stmt.body.clone(), environment)? {
+                ControlFlow::Break(label) if is_loop_target(&stmt.label, &label) => break,
+                ControlFlow::Continue(label) if is_loop_target(&stmt.label, &label) => {}
+                ControlFlow::Normal => {}
+                flow => return Ok(flow),
+            }
+        }
+
+        Ok(ControlFlow::Normal)
     }
 
-    fn visit_function_stmt(&self, stmt: &FunctionStmt) -> Result<(), LoxError> {
-        let function = LoxFunction::new(stmt);
+    fn visit_function_stmt(&self, stmt: &FunctionStmt) -> Result<ControlFlow, LoxError> {
+        let function = LoxFunction::new(stmt, &self.environment.borrow(), false);
 
         self.environment.borrow_mut().as_ref().borrow_mut().define(&stmt.name.lexeme, Object::Func(Rc::new(function)));
-        Ok()
+        Ok(ControlFlow::Normal)
+    }
+
+    fn visit_class_stmt(&self, stmt: &ClassStmt) -> Result<ControlFlow, LoxError> {
+        let superclass = match stmt.superclass.clone() {
+            Some(superclass_expr) => {
+                match self.evaluate(superclass_expr.clone())? {
+                    Object::Class(superclass) => Some(superclass),
+                    _ => {
+                        let name = match superclass_expr.as_ref() {
+                            Expr::Variable(variable) => variable.name.clone(),
+                            _ => stmt.name.clone()
+                        };
+                        return Err(LoxError::runtime_error(&name, ErrorCode::InvalidSuperclass, String::from("Superclass must be a class.")))
+                    }
+                }
+            }
+            None => None
+        };
+
+        self.environment.borrow().borrow_mut().define(&stmt.name.lexeme, Object::Nil);
+
+        let enclosing = self.environment.borrow().clone();
+        if let Some(superclass) = &superclass {
+            let mut environment = Environment::new_enclosing(Rc::clone(&enclosing));
+            environment.define("super", Object::Class(Rc::clone(superclass)));
+            self.environment.replace(Rc::new(RefCell::new(environment)));
+        }
+
+        let class = self.class_members(stmt, &superclass).map(|members| {
+            LoxClass::new(&stmt.name.lexeme, superclass, members, has_modifier(&stmt.modifiers, DYNAMIC), &self.environment.borrow())
+        });
+
+        self.environment.replace(enclosing);
+        self.environment.borrow().borrow_mut().assign(&stmt.name, Object::Class(Rc::new(class?)))?;
+        Ok(ControlFlow::Normal)
     }
 
-    fn visit_break_stmt(&self, _stmt: &BreakStmt) -> Result<(), LoxError> {
-        Err(LoxError::break_error())
+    fn visit_break_stmt(&self, stmt: &BreakStmt) -> Result<ControlFlow, LoxError> {
+        Ok(ControlFlow::Break(stmt.label.as_ref().map(|label| label.lexeme.clone())))
     }
 
-    fn visit_continue_stmt(&self, _stmt: &ContinueStmt) -> Result<(), LoxError> {
-        Err(LoxError::continue_error())
+    fn visit_continue_stmt(&self, stmt: &ContinueStmt) -> Result<ControlFlow, LoxError> {
+        Ok(ControlFlow::Continue(stmt.label.as_ref().map(|label| label.lexeme.clone())))
+    }
+
+    fn visit_match_stmt(&self, stmt: &MatchStmt) -> Result<ControlFlow, LoxError> {
+        let value = self.evaluate(stmt.subject.clone())?;
+        match self.match_arm(&value, &stmt.arms) {
+            // an unlabeled break leaves the match rather than an enclosing loop
+            Some(arm) => match self.execute(arm.body.clone())? {
+                ControlFlow::Break(None) => Ok(ControlFlow::Normal),
+                flow => Ok(flow),
+            }
+            None => Ok(ControlFlow::Normal)
+        }
     }
 
-    fn visit_print_stmt(&self, stmt: &PrintStmt) -> Result<(), LoxError>{
+    fn visit_print_stmt(&self, stmt: &PrintStmt) -> Result<ControlFlow, LoxError>{
         let value = self.evaluate(stmt.expression.clone())?;
         println!("{:?}", value);
-        return Ok(());
+        return Ok(ControlFlow::Normal);
+    }
+
+    fn visit_return_stmt(&self, stmt: &ReturnStmt) -> Result<ControlFlow, LoxError> {
+        // a call in tail position is made by the caller's trampoline, after this frame is gone
+        if let Some(Expr::Call(call)) = stmt.value.as_deref() {
+            return Ok(ControlFlow::TailCall(self.bind_call(call)?));
+        }
+
+        let value = match stmt.value.clone() {
+            Some(value) => self.evaluate(value)?,
+            None => Object::Nil
+        };
+
+        Ok(ControlFlow::Return(value))
     }
 
-    fn visit_while_stmt(&self, stmt: &WhileStmt) -> Result<(), LoxError> {
+    fn visit_while_stmt(&self, stmt: &WhileStmt) -> Result<ControlFlow, LoxError> {
         while self.is_truthy(&self.evaluate(stmt.condition.clone())?) {
-            match self.execute(stmt.body.clone()) {
-                Err(LoxError::BreakError) => break,
-                Err(LoxError::ContinueError) => {
-                    if stmt.is_for_loop {
-                        let _body = stmt.body.as_ref();
-                        //TODO: Im not sure if this is possible with my code setup???
-                    }
-                },
-                Err(e) => return Err(e),
-                Ok(_) => {}
+            match self.execute(stmt.body.clone())? {
+                ControlFlow::Break(label) if is_loop_target(&stmt.label, &label) => break,
+                ControlFlow::Continue(label) if is_loop_target(&stmt.label, &label) => {}
+                ControlFlow::Normal => {}
+                // a labeled break or continue for an outer loop, or a return
+                flow => return Ok(flow),
+            }
+
+            // a for loop's increment also runs after 'continue'
+            if let Some(increment) = stmt.increment.clone() {
+                self.evaluate(increment)?;
             }
         }
 
-        Ok(())
-    
+        Ok(ControlFlow::Normal)
+    }
+
+    // the condition is checked after each run of the body, including after 'continue'
+    fn visit_do_while_stmt(&self, stmt: &DoWhileStmt) -> Result<ControlFlow, LoxError> {
+        loop {
+            match self.execute(stmt.body.clone())? {
+                ControlFlow::Break(label) if is_loop_target(&stmt.label, &label) => break,
+                ControlFlow::Continue(label) if is_loop_target(&stmt.label, &label) => {}
+                ControlFlow::Normal => {}
+                flow => return Ok(flow),
+            }
+
+            if !self.is_truthy(&self.evaluate(stmt.condition.clone())?) {
+                break;
+            }
+        }
+
+        Ok(ControlFlow::Normal)
     }
 
-    fn visit_var_stmt(&self, stmt: &VarStmt) -> Result<(), LoxError>{
+    fn visit_loop_stmt(&self, stmt: &LoopStmt) -> Result<ControlFlow, LoxError> {
+        loop {
+            match self.execute(stmt.body.clone())? {
+                ControlFlow::Break(label) if is_loop_target(&stmt.label, &label) => break,
+                ControlFlow::Continue(label) if is_loop_target(&stmt.label, &label) => {}
+                ControlFlow::Normal => {}
+                flow => return Ok(flow),
+            }
+        }
+
+        Ok(ControlFlow::Normal)
+    }
+
+    fn visit_var_stmt(&self, stmt: &VarStmt) -> Result<ControlFlow, LoxError>{
         let value = if let Some(initializer) = stmt.initializer.clone() {
             self.evaluate(initializer)?
         } else {
@@ -164,22 +521,21 @@ impl StmtVisitor<()> for Interpreter {
         };
     
         self.environment.borrow().borrow_mut().define(&stmt.name.lexeme.to_owned(), value);
-        Ok(())
+        Ok(ControlFlow::Normal)
     }
 
-    fn visit_block_stmt(&self, stmt: &BlockStmt) -> Result<(), LoxError>{ //enclosing: Rc<RefCell<Environment>>
+    fn visit_block_stmt(&self, stmt: &BlockStmt) -> Result<ControlFlow, LoxError>{ //enclosing: Rc<RefCell<Environment>>
         let re_init = Environment::new_enclosing(self.environment.borrow().clone());
-        self.execute_block(&stmt.statements, re_init )? ;
-        return Ok(());
+        return self.execute_block(&stmt.statements, Rc::new(RefCell::new(re_init)));
     }
 
-    fn visit_if_stmt(&self, stmt: &IfStmt) -> Result<(), LoxError> {
+    fn visit_if_stmt(&self, stmt: &IfStmt) -> Result<ControlFlow, LoxError> {
         let eval = self.evaluate(stmt.condition.clone())?;
         match self.is_truthy(&eval) {
             true => self.execute(stmt.then_branch.clone()),
             false => {
                 match stmt.else_branch.clone() {
-                    None => Ok(()),
+                    None => Ok(ControlFlow::Normal),
                     Some(x) => {
                         self.execute(x)
     }}}}} // so ugly but it bothered me 
@@ -188,31 +544,161 @@ impl StmtVisitor<()> for Interpreter {
 impl ExprVisitor<Object> for Interpreter {
 
     fn visit_call_expr(&self, expr: &CallExpr) -> Result<Object, LoxError> {
-        let callee = self.evaluate(expr.callee.clone())?;
+        let call = self.bind_call(expr)?;
+        self.call(call, &expr.paren)
+    }
 
-        let mut arguments = Vec::new();
+    fn visit_array_expr(&self, expr: &ArrayExpr) -> Result<Object, LoxError> {
+        let mut elements = Vec::new();
+        for element in expr.elements.iter() {
+            elements.push(self.evaluate(element.clone())?);
+        }
+        Ok(Object::Array(Rc::new(RefCell::new(LoxArray::from(elements)))))
+    }
 
-        for argument in expr.arguments.clone() {
-            arguments.push(self.evaluate(argument)?);
+    fn visit_interpolation_expr(&self, expr: &InterpolationExpr) -> Result<Object, LoxError> {
+        let mut result = String::new();
+        for part in expr.parts.iter() {
+            result.push_str(&self.evaluate(part.clone())?.to_string());
         }
-        
-        if let Object::Func(function) = callee {
-            if arguments.len() != function.function.arity(){
-                Err(LoxError::runtime_error(&expr.paren, String::from(format!("Expectd {} but got {}.", 
-                    function.function.arity(), 
-                    arguments.len()))))
-                } else {
-                    Ok(function.function.call(self, arguments)?)
+        Ok(Object::String(result))
+    }
+
+    fn visit_map_expr(&self, expr: &MapExpr) -> Result<Object, LoxError> {
+        let mut map = LoxMap::new();
+        for (key, value) in expr.keys.iter().zip(expr.values.iter()) {
+            let key = self.evaluate(key.clone())?;
+            let key = self.map_key(&expr.brace, &key)?;
+            map.insert(key, self.evaluate(value.clone())?);
+        }
+        Ok(Object::Map(Rc::new(RefCell::new(map))))
+    }
+
+    fn visit_match_expr(&self, expr: &MatchExpr) -> Result<Object, LoxError> {
+        let value = self.evaluate(expr.subject.clone())?;
+        match self.match_arm(&value, &expr.arms) {
+            Some(arm) => self.evaluate(arm.body.clone()),
+            None => Err(LoxError::runtime_error(&expr.keyword, ErrorCode::NoMatchingArm, String::from(format!("No match arm matches {value:?}."))))
+        }
+    }
+
+    fn visit_index_expr(&self, expr: &IndexExpr) -> Result<Object, LoxError> {
+        let object = self.evaluate(expr.object.clone())?;
+        let index = self.evaluate(expr.index.clone())?;
+
+        match object {
+            Object::Array(array) => {
+                let array = array.borrow();
+                let i = self.array_index(&expr.bracket, &array, &index)?;
+                Ok(array[i].clone())
+            }
+            Object::Map(map) => {
+                let key = self.map_key(&expr.bracket, &index)?;
+                self.map_get(&expr.bracket, &map.borrow(), &key)
+            }
+            _ => Err(LoxError::runtime_error(&expr.bracket, ErrorCode::NotIndexable, String::from("Only arrays and maps can be indexed.")))
+        }
+    }
+
+    fn visit_index_set_expr(&self, expr: &IndexSetExpr) -> Result<Object, LoxError> {
+        let object = self.evaluate(expr.object.clone())?;
+        let index = self.evaluate(expr.index.clone())?;
+        let value = self.evaluate(expr.value.clone())?;
+
+        match object {
+            Object::Array(array) => {
+                let i = self.array_index(&expr.bracket, &array.borrow(), &index)?;
+                array.borrow_mut()[i] = value.clone();
+                Ok(value)
+            }
+            Object::Map(map) => {
+                let key = self.map_key(&expr.bracket, &index)?;
+                map.borrow_mut().insert(key, value.clone());
+                Ok(value)
+            }
+            _ => Err(LoxError::runtime_error(&expr.bracket, ErrorCode::NotIndexable, String::from("Only arrays and maps can be indexed.")))
+        }
+    }
+
+    fn visit_get_expr(&self, expr: &GetExpr) -> Result<Object, LoxError> {
+        let via_this = matches!(expr.object.as_ref(), Expr::This(_));
+
+        match self.evaluate(expr.object.clone())? {
+            Object::Instance(instance) => LoxInstance::get(&instance, &expr.name, via_this),
+            Object::Class(class) => class.get_static(&expr.name),
+            Object::Map(map) => self.map_get(&expr.name, &map.borrow(), &MapKey::String(expr.name.lexeme.clone())),
+            _ => Err(LoxError::runtime_error(&expr.name, ErrorCode::NotAnInstance, String::from("Only instances have properties.")))
+        }
+    }
+
+    fn visit_lambda_expr(&self, expr: &LambdaExpr) -> Result<Object, LoxError> {
+        Ok(Object::Func(Rc::new(LoxFunction::new(&expr.function, &self.environment.borrow(), false))))
+    }
+
+    fn visit_range_expr(&self, expr: &RangeExpr) -> Result<Object, LoxError> {
+        let start = self.evaluate(expr.start.clone())?;
+        let end = self.evaluate(expr.end.clone())?;
+
+        match (start, end) {
+            (Object::Number(start), Object::Number(end)) => Ok(Object::Range(start, end, expr.operator.t_type == DOT_DOT_EQUAL)),
+            _ => Err(LoxError::runtime_error(&expr.operator, ErrorCode::InvalidOperand, String::from("Range bounds must be numbers.")))
+        }
+    }
+
+    fn visit_set_expr(&self, expr: &SetExpr) -> Result<Object, LoxError> {
+        let via_this = matches!(expr.object.as_ref(), Expr::This(_));
+        let object = self.evaluate(expr.object.clone())?;
+
+        match object {
+            Object::Instance(instance) => {
+                let value = self.evaluate(expr.value.clone())?;
+                instance.set(&expr.name, value.clone(), via_this)?;
+                Ok(value)
+            }
+            Object::Class(class) => {
+                let value = self.evaluate(expr.value.clone())?;
+                class.set_static(&expr.name, value.clone())?;
+                Ok(value)
+            }
+            Object::Map(map) => {
+                let value = self.evaluate(expr.value.clone())?;
+                map.borrow_mut().insert(MapKey::String(expr.name.lexeme.clone()), value.clone());
+                Ok(value)
+            }
+            _ => Err(LoxError::runtime_error(&expr.name, ErrorCode::NotAnInstance, String::from("Only instances have fields.")))
+        }
+    }
+
+    fn visit_super_expr(&self, expr: &SuperExpr) -> Result<Object, LoxError> {
+        let distance = match self.locals.borrow().get(&Interpreter::expr_key(expr)) {
+            Some(distance) => *distance,
+            None => return Err(LoxError::runtime_error(&expr.keyword, ErrorCode::MisplacedThisOrSuper, String::from("Can't use 'super' outside of a class.")))
+        };
+
+        let superclass = self.environment.borrow().borrow().get_at(distance, &expr.keyword)?;
+        let this = Token::new(THIS, String::from("this"), None, expr.keyword.span);
+        let object = self.environment.borrow().borrow().get_at(distance - 1, &this)?;
+
+        match (superclass, object) {
+            (Object::Class(superclass), Object::Instance(instance)) => {
+                match superclass.find_method(&expr.method.lexeme) {
+                    Some(method) => Ok(Object::Func(Rc::new(method.bind(&instance)))),
+                    None => Err(LoxError::runtime_error(&expr.method, ErrorCode::UndefinedProperty, String::from(format!("Undefined property '{}'.", expr.method.lexeme))))
                 }
             }
-        else {
-            Err(LoxError::runtime_error(&expr.paren, String::from("Can only call functions or classes")))
+            _ => Err(LoxError::runtime_error(&expr.keyword, ErrorCode::MisplacedThisOrSuper, String::from("Can't use 'super' outside of a class.")))
         }
+    }
 
+    fn visit_this_expr(&self, expr: &ThisExpr) -> Result<Object, LoxError> {
+        self.look_up_variable(&expr.keyword, Interpreter::expr_key(expr))
     }
 
     fn visit_literal_expr(&self, expr: &LiteralExpr) -> Result<Object, LoxError>{
-        Ok(expr.value.clone().unwrap())
+        match &expr.value {
+            Some(value) => Ok(value.clone()),
+            None => Err(LoxError::internal_error(String::from("Literal has no value."), Some(expr.span)))
+        }
     }
 
     fn visit_logical_expr(&self, expr: &LogicalExpr) -> Result<Object, LoxError> {
@@ -221,11 +707,9 @@ impl ExprVisitor<Object> for Interpreter {
         if expr.operator.t_type == OR {
             if self.is_truthy(&left.clone().borrow()) {
                 return Ok(left.into_inner());
-            } else {}
-        } else {
-            if !self.is_truthy(&left.clone().borrow()) {
-                return Ok(left.into_inner());
-            } else {}
+            }
+        } else if !self.is_truthy(&left.clone().borrow()) {
+            return Ok(left.into_inner());
         }
         Ok(self.evaluate(expr.right.clone())?)
     }
@@ -249,7 +733,7 @@ impl ExprVisitor<Object> for Interpreter {
                 return Ok(Object::Bool(!self.is_truthy(&right)))
             }
             _ => {
-                Err(LoxError::null()) //unreachable after implementing all matches hopefully
+                Err(LoxError::internal_error(format!("Unknown unary operator '{}'.", expr.operator.lexeme), Some(expr.span)))
             }
         }
     }
@@ -266,7 +750,7 @@ impl ExprVisitor<Object> for Interpreter {
                         return Ok(Object::Number(left - right))
                     }
                 }
-                return Err(LoxError::null())
+                return Err(self.unchecked_operands(expr))
             }
             SLASH => {
                 self.check_number_operands(expr.operator.clone(), &left, &right)?;
@@ -275,7 +759,7 @@ impl ExprVisitor<Object> for Interpreter {
                         return Ok(Object::Number(left / right))
                     }
                 }
-                return Err(LoxError::null())
+                return Err(self.unchecked_operands(expr))
             }
             STAR => {
                 self.check_number_operands(expr.operator.clone(), &left, &right)?;
@@ -284,7 +768,7 @@ impl ExprVisitor<Object> for Interpreter {
                         return Ok(Object::Number(left * right))
                     }
                 }
-                return Err(LoxError::null())
+                return Err(self.unchecked_operands(expr))
             }
             PLUS => {
                 match (left.clone(), right.clone()) {
@@ -295,7 +779,7 @@ impl ExprVisitor<Object> for Interpreter {
                         Ok(Object::String(x+&y))
                     },
                     _ => {
-                        return Err(LoxError::runtime_error(&expr.operator, String::from("Operands must be two numbers or two strings.")))
+                        return Err(LoxError::runtime_error(&expr.operator, ErrorCode::InvalidOperand, String::from("Operands must be two numbers or two strings.")))
                     }
                 }
             }
@@ -306,7 +790,7 @@ impl ExprVisitor<Object> for Interpreter {
                         return Ok(Object::Bool(left > right))
                     }
                 }
-                return Err(LoxError::null())
+                return Err(self.unchecked_operands(expr))
             },
             GREATER_EQUAL => {
                 self.check_number_operands(expr.operator.clone(), &left, &right)?;
@@ -315,7 +799,7 @@ impl ExprVisitor<Object> for Interpreter {
                         return Ok(Object::Bool(left >= right))
                     }
                 }
-                return Err(LoxError::null())
+                return Err(self.unchecked_operands(expr))
             },
             LESS => {
                 self.check_number_operands(expr.operator.clone(), &left, &right)?;
@@ -324,7 +808,7 @@ impl ExprVisitor<Object> for Interpreter {
                         return Ok(Object::Bool(left < right))
                     }
                 }
-                return Err(LoxError::null())
+                return Err(self.unchecked_operands(expr))
             },
             LESS_EQUAL => {
                 self.check_number_operands(expr.operator.clone(), &left, &right)?;
@@ -333,7 +817,7 @@ impl ExprVisitor<Object> for Interpreter {
                         return Ok(Object::Bool(left <= right))
                     }
                 }
-                return Err(LoxError::null())
+                return Err(self.unchecked_operands(expr))
             },
             BANG_EQUAL => {
                 return Ok(Object::Bool(!self.is_equal(left, right)))
@@ -342,7 +826,7 @@ impl ExprVisitor<Object> for Interpreter {
                 return Ok(Object::Bool(self.is_equal(left, right)))
             },
             _ => {
-                Err(LoxError::Null)
+                Err(LoxError::internal_error(format!("Unknown binary operator '{}'.", expr.operator.lexeme), Some(expr.span)))
             }
 
             
@@ -350,14 +834,41 @@ impl ExprVisitor<Object> for Interpreter {
     }
 
     fn visit_variable_expr(&self, expr: &VariableExpr) -> Result<Object, LoxError> {
-        Ok(self.environment.borrow().borrow_mut().get(&expr.name)?)
+        self.look_up_variable(&expr.name, Interpreter::expr_key(expr))
     }
 
     fn visit_assign_expr(&self, expr: &AssignExpr) -> Result<Object, LoxError> {
         let value = self.evaluate(expr.value.clone());
 
-        self.environment.borrow().borrow_mut().assign(&expr.name.clone(), value.clone()?)?;
+        match self.locals.borrow().get(&Interpreter::expr_key(expr)) {
+            Some(distance) => self.environment.borrow().borrow_mut().assign_at(*distance, &expr.name, value.clone()?)?,
+            None => self.globals.borrow_mut().assign(&expr.name, value.clone()?)?
+        }
 
         return Ok(value?);
     }
 }   
+
+// an unlabeled break or continue targets the innermost loop
+fn is_loop_target(own: &Option<Token>, label: &Option<String>) -> bool {
+    match (label, own) {
+        (None, _) => true,
+        (Some(label), Some(own)) => *label == own.lexeme,
+        (Some(_), None) => false,
+    }
+}
+
+// how far down the native stack the caller is
+#[inline(never)]
+fn stack_address() -> usize {
+    let local = 0u8;
+    std::hint::black_box(&local) as *const u8 as usize
+}
+
+// natives don't know where they were called from
+fn at_call_site(error: LoxError, span: Span) -> LoxError {
+    match error {
+        LoxError::NativeError {name, code, message, span: None, trace} => LoxError::NativeError {name, code, message, span: Some(span), trace},
+        error => error
+    }
+}
@@ -1,11 +1,13 @@
 
 use std::{fs, io};
-use std::io::Write;
+use std::io::{IsTerminal, Write};
 use std::process::exit;
 use crate::error::*;
+use crate::diagnostic::*;
 use crate::scanner;
 use crate::parser::Parser;
 use crate::interpreter::Interpreter;
+use crate::resolver::Resolver;
 //use std::rc::Rc;
 
 //mod scanner;
@@ -13,29 +15,40 @@ use crate::interpreter::Interpreter;
 #[derive(Clone, Debug)]
 pub struct Lox {
     had_error: bool,
-    interpreter: Interpreter
+    interpreter: Interpreter,
+    // every source run so far; a span's file is an index into this
+    sources: Vec<SourceFile>,
+    error_format: ErrorFormat,
+    color: bool
 }
 
 impl Lox {
-    pub fn new() -> Self {
+    pub fn new(error_format: ErrorFormat) -> Self {
         Lox {
             had_error: false,
-            interpreter: Interpreter::new()
+            interpreter: Interpreter::new(),
+            sources: Vec::new(),
+            error_format,
+            color: io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none()
         }
     }
 
 
-    pub fn run_file (&mut self, path: &String) -> io::Result<()>{
-        let code = fs::read_to_string(path).unwrap();
+    pub fn run_file (&mut self, path: &String) {
+        let result = match fs::read_to_string(path) {
+            Ok(code) => self.run(path, code),
+            Err(e) => Err(LoxError::io_error(format!("Could not read '{path}': {e}.")))
+        };
 
-        match self.run(code) {
-            Ok(_) => {},
-            Err(mut e) => {
-                e.report(String::from(""));
-                exit(0);
-            }
+        if let Err(e) = result {
+            self.report(&e);
+            // sysexits codes, as clox uses
+            exit(match e {
+                LoxError::ParseFailure {..} => 65,
+                LoxError::IoError {..} => 66,
+                _ => 70
+            });
         }
-        Ok(())
     }
 
     pub fn run_prompt (&mut self) {
@@ -43,13 +56,17 @@ impl Lox {
 
         loop {
             print!("> ");
-            io::stdout().flush().unwrap();
+            if io::stdout().flush().is_err() {
+                break;
+            }
             let mut buffer = String::new();
             let line = input.read_line(&mut buffer);
             match line {
                 Ok(0) => break,
                 Ok(_) => {
-                    self.run(buffer);
+                    if let Err(e) = self.run("<repl>", buffer) {
+                        self.report(&e);
+                    }
                     self.had_error = false;
                 }
                 _ => break,
@@ -57,18 +74,47 @@ impl Lox {
         }
     }
 
-    pub fn run(&mut self, source: String) -> Result<(), LoxError>{
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.interpreter.max_call_depth = depth;
+    }
+
+    pub fn set_stack_size(&mut self, size: usize) {
+        self.interpreter.set_stack_size(size);
+    }
+
+    pub fn report(&self, error: &LoxError) {
+        let diagnostics = error.diagnostics();
+        Renderer::new(&self.sources, self.error_format, self.color).emit(&diagnostics);
+    }
+
+    pub fn run(&mut self, name: &str, source: String) -> Result<(), LoxError>{
         // println!("{}", source);
-        let mut scanner = scanner::Scanner::new(source);
-        let tokens = scanner.scan_tokens()?;
+        let file = self.sources.len();
+        self.sources.push(SourceFile{name: name.to_string(), text: source.clone()});
+        let mut scanner = scanner::Scanner::new(source, file);
+        let (tokens, mut errors) = scanner.scan_tokens();
 
         let mut parser = Parser::new(tokens);
 
-        let statements = parser.parse();
+        let statements = match parser.parse() {
+            Ok(statements) if errors.is_empty() => statements,
+            Ok(_) => return Err(LoxError::parse_failure(errors)),
+            Err(parse_errors) => {
+                // the scanner and the parser each report in order, merge them back into source order
+                errors.extend(parse_errors);
+                errors.sort_by_key(|e| e.span().map(|span| span.start));
+                return Err(LoxError::parse_failure(errors));
+            }
+        };
 
         //println!("{:?}\n", parser.tokens);
+
+        let resolver = Resolver::new(&self.interpreter);
+        let resolved = resolver.resolve(&statements);
+        Renderer::new(&self.sources, self.error_format, self.color).emit(&resolver.warnings());
+        resolved.map_err(LoxError::parse_failure)?;
         
-        self.interpreter.interpret(statements?)?;
+        self.interpreter.interpret(statements)?;
 
     
         return Ok(());
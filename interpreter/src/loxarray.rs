@@ -0,0 +1,33 @@
+use crate::object::*;
+
+use std::ops::{Deref, DerefMut};
+
+// a Vec that drops arrays and maps nested in it one after another instead of one inside the next
+#[derive(Default, PartialEq)]
+pub struct LoxArray(Vec<Object>);
+
+impl From<Vec<Object>> for LoxArray {
+    fn from(elements: Vec<Object>) -> Self {
+        LoxArray(elements)
+    }
+}
+
+impl Deref for LoxArray {
+    type Target = Vec<Object>;
+
+    fn deref(&self) -> &Vec<Object> {
+        &self.0
+    }
+}
+
+impl DerefMut for LoxArray {
+    fn deref_mut(&mut self) -> &mut Vec<Object> {
+        &mut self.0
+    }
+}
+
+impl Drop for LoxArray {
+    fn drop(&mut self) {
+        drop_contents(std::mem::take(&mut self.0));
+    }
+}
@@ -1,72 +1,25 @@
-use std::fmt;
-use std::rc::Rc;
-use std::time::SystemTime;
-use core::fmt::{Debug, Display};
-
 use crate::interpreter::*;
 use crate::object::*;
 use crate::error::*;
-
-#[derive(Clone)]
-pub struct Callable {
-    pub function: Rc<dyn LoxCallable>,
-    pub arity: usize
-}
-
-// impl Callable {
-//     pub fn new() -> Callable {
-//         Callable {
-//             function: Rc::new(LoxCallable::new()),
-//             arity: 0        
-//         }
-//     }
-// }
-
-impl Debug for Callable {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "<callable>")
-    }
-}
-
-
-impl Display for Callable {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "<Callable>")
-    }
-}
-
-impl PartialEq for Callable {
-    fn eq(&self, other: &Self) -> bool {
-        Rc::ptr_eq(&self.function, &other.function)
-    }
-}
-
+use crate::diagnostic::Label;
+use crate::signature::Signature;
+use crate::controlflow::ControlFlow;
 
 pub trait LoxCallable {
     fn call(&self, interpreter: &Interpreter, arguments: Vec<Object>) ->  Result<Object, LoxError>;
-    fn arity(&self) -> usize;
-}
-
-impl LoxCallable for Callable {
-    fn call(&self, interpreter: &Interpreter, arguments: Vec<Object>) ->  Result<Object, LoxError> {
-        self.function.call(interpreter, arguments)
+    fn signature(&self) -> Signature;
+    // how the callable shows up in a backtrace
+    fn name(&self) -> String;
+    // arguments as bound to the signature, None where a parameter's default applies
+    fn call_bound(&self, interpreter: &Interpreter, arguments: Vec<Option<Object>>) -> Result<Object, LoxError> {
+        self.call(interpreter, arguments.into_iter().map(|argument| argument.unwrap_or(Object::Nil)).collect())
     }
-    fn arity(&self) -> usize {
-        self.arity
+    // runs the callable up to a tail call, which the interpreter makes in its place
+    fn call_frame(&self, interpreter: &Interpreter, arguments: Vec<Option<Object>>) -> Result<ControlFlow, LoxError> {
+        self.call_bound(interpreter, arguments).map(ControlFlow::Return)
     }
-}
-
-pub struct NativeClock;
-
-impl LoxCallable for NativeClock {
-    fn call(&self, _interpreter: &Interpreter, _arguments: Vec<Object>) ->  Result<Object, LoxError>{
-        match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
-            Ok(n) => Ok(Object::Number(n.as_millis() as f64)),
-            Err(e) => Err(LoxError::null()) //TODO: make error for clock
-        }
+    // where the callable was declared, for pointing at it in errors
+    fn declaration(&self) -> Option<Label> {
+        None
     }
-
-    fn arity(&self) -> usize {
-        0
-    }
-}
\ No newline at end of file
+}
@@ -0,0 +1,197 @@
+use crate::object::*;
+use crate::token::*;
+use crate::tokentype::TokenType;
+use crate::stmt::*;
+use crate::loxcallable::*;
+use crate::loxfunction::*;
+use crate::loxinstance::*;
+use crate::interpreter::*;
+use crate::environment::*;
+use crate::error::*;
+use crate::diagnostic::{Label, ErrorCode};
+use crate::signature::Signature;
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+pub fn has_modifier(modifiers: &[Token], modifier: TokenType) -> bool {
+    modifiers.iter().any(|token| token.t_type == modifier)
+}
+
+#[derive(Default)]
+pub struct ClassMembers {
+    pub methods: HashMap<String, Rc<LoxFunction>>,
+    pub static_methods: HashMap<String, Rc<LoxFunction>>,
+    pub statics: HashMap<String, Object>,
+    // instance fields are initialized again for every new instance
+    pub fields: Vec<Rc<VarStmt>>,
+    pub private: HashSet<String>,
+}
+
+pub struct LoxClass {
+    pub name: String,
+    superclass: Option<Rc<LoxClass>>,
+    methods: HashMap<String, Rc<LoxFunction>>,
+    static_methods: HashMap<String, Rc<LoxFunction>>,
+    statics: RefCell<HashMap<String, Object>>,
+    fields: Vec<Rc<VarStmt>>,
+    private: HashSet<String>,
+    dynamic: bool,
+    closure: Rc<RefCell<Environment>>
+}
+
+impl PartialEq for LoxClass {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl fmt::Debug for LoxClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl fmt::Display for LoxClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl LoxClass {
+    pub fn new(name: &str, superclass: Option<Rc<LoxClass>>, members: ClassMembers, dynamic: bool, closure: &Rc<RefCell<Environment>>) -> Self {
+        LoxClass {
+            name: name.to_string(),
+            superclass,
+            methods: members.methods,
+            static_methods: members.static_methods,
+            statics: RefCell::new(members.statics),
+            fields: members.fields,
+            private: members.private,
+            dynamic,
+            closure: Rc::clone(closure)
+        }
+    }
+
+    pub fn find_method(&self, name: &str) -> Option<Rc<LoxFunction>> {
+        if let Some(method) = self.methods.get(name) {
+            return Some(Rc::clone(method));
+        }
+        match &self.superclass {
+            Some(superclass) => superclass.find_method(name),
+            None => None
+        }
+    }
+
+    pub fn is_private(&self, name: &str) -> bool {
+        if self.private.contains(name) {
+            return true;
+        }
+        match &self.superclass {
+            Some(superclass) => superclass.is_private(name),
+            None => false
+        }
+    }
+
+    // a class that declares fields only accepts those, unless it or an ancestor is dynamic
+    pub fn is_sealed(&self) -> bool {
+        !self.is_dynamic() && self.declares_fields()
+    }
+
+    fn is_dynamic(&self) -> bool {
+        self.dynamic || self.superclass.as_ref().is_some_and(|superclass| superclass.is_dynamic())
+    }
+
+    fn declares_fields(&self) -> bool {
+        !self.fields.is_empty() || self.superclass.as_ref().is_some_and(|superclass| superclass.declares_fields())
+    }
+
+    fn find_static_class(&self, name: &str) -> Option<&LoxClass> {
+        if self.statics.borrow().contains_key(name) || self.static_methods.contains_key(name) {
+            return Some(self);
+        }
+        match &self.superclass {
+            Some(superclass) => superclass.find_static_class(name),
+            None => None
+        }
+    }
+
+    pub fn get_static(&self, name: &Token) -> Result<Object, LoxError> {
+        if let Some(class) = self.find_static_class(&name.lexeme) {
+            if let Some(value) = class.statics.borrow().get(&name.lexeme) {
+                return Ok(value.clone());
+            }
+            if let Some(method) = class.static_methods.get(&name.lexeme) {
+                return Ok(Object::Func(Rc::clone(method)));
+            }
+        }
+        Err(LoxError::runtime_error(name, ErrorCode::UndefinedProperty, String::from(format!("Undefined static member '{}' on class '{}'.", name.lexeme, self.name))))
+    }
+
+    pub fn set_static(&self, name: &Token, value: Object) -> Result<(), LoxError> {
+        match self.find_static_class(&name.lexeme) {
+            Some(class) if class.statics.borrow().contains_key(&name.lexeme) => {
+                class.statics.borrow_mut().insert(name.lexeme.clone(), value);
+                Ok(())
+            }
+            _ => Err(LoxError::runtime_error(name, ErrorCode::UndefinedProperty, String::from(format!("Undefined static field '{}' on class '{}'.", name.lexeme, self.name))))
+        }
+    }
+
+    fn initialize_fields(&self, interpreter: &Interpreter, instance: &Rc<LoxInstance>) -> Result<(), LoxError> {
+        if let Some(superclass) = &self.superclass {
+            superclass.initialize_fields(interpreter, instance)?;
+        }
+
+        for field in self.fields.iter() {
+            let value = match field.initializer.clone() {
+                Some(initializer) => {
+                    let mut environment = Environment::new_enclosing(Rc::clone(&self.closure));
+                    environment.define("this", Object::Instance(Rc::clone(instance)));
+                    interpreter.evaluate_in(initializer, Rc::new(RefCell::new(environment)))?
+                }
+                None => Object::Nil
+            };
+            instance.define(&field.name.lexeme, value);
+        }
+        Ok(())
+    }
+}
+
+// calling a class constructs an instance, which needs a handle on the class itself
+impl LoxCallable for Rc<LoxClass> {
+    fn call(&self, interpreter: &Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        self.call_bound(interpreter, arguments.into_iter().map(Some).collect())
+    }
+
+    fn call_bound(&self, interpreter: &Interpreter, arguments: Vec<Option<Object>>) -> Result<Object, LoxError> {
+        let instance = Rc::new(LoxInstance::new(Rc::clone(self)));
+
+        self.initialize_fields(interpreter, &instance)?;
+
+        if let Some(initializer) = self.find_method("init") {
+            initializer.bind(&instance).call_bound(interpreter, arguments)?;
+        }
+        Ok(Object::Instance(instance))
+    }
+
+    fn signature(&self) -> Signature {
+        match self.find_method("init") {
+            Some(initializer) => initializer.signature(),
+            None => Signature::default()
+        }
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn declaration(&self) -> Option<Label> {
+        let initializer = self.find_method("init")?;
+        let mut label = initializer.declaration()?;
+        label.message = String::from("initializer declared here");
+        Some(label)
+    }
+}
@@ -5,18 +5,26 @@ use crate::loxcallable::*;
 use crate::interpreter::*;
 use crate::environment::*;
 use crate::error::*;
+use crate::controlflow::ControlFlow;
+use crate::diagnostic::Label;
+use crate::loxinstance::*;
+use crate::loxarray::LoxArray;
+use crate::tokentype::TokenType;
+use crate::signature::*;
 
 
 use std::rc::Rc;
+use std::cell::RefCell;
 use std::fmt;
-use std::time::SystemTime;
 
 
 
 pub struct LoxFunction {
     name: Token,
-    params: Rc<Vec<Token>>,
-    body: Rc<Vec<Rc<Stmt>>>
+    params: Rc<Vec<Parameter>>,
+    body: Rc<Vec<Rc<Stmt>>>,
+    closure: Rc<RefCell<Environment>>,
+    is_initializer: bool
 }
 
 impl PartialEq for LoxFunction {
@@ -24,7 +32,7 @@ impl PartialEq for LoxFunction {
         self.name.t_type == other.name.t_type
             && Rc::ptr_eq(&self.params, &other.params)
             && Rc::ptr_eq(&self.body, &other.body)
-            // && Rc::ptr_eq(&self.closure, &other.closure)
+            && Rc::ptr_eq(&self.closure, &other.closure)
     }
 }
 
@@ -41,45 +49,77 @@ impl fmt::Display for LoxFunction {
 }
 
 impl LoxFunction{
-    pub fn new(declaration: &FunctionStmt) -> Self {
+    pub fn new(declaration: &FunctionStmt, closure: &Rc<RefCell<Environment>>, is_initializer: bool) -> Self {
         Self {
-            name: declaration.name,
+            name: declaration.name.clone(),
             params: Rc::clone(&declaration.params),
-            body: Rc::clone(&declaration.body)
+            body: Rc::clone(&declaration.body),
+            closure: Rc::clone(closure),
+            is_initializer
         }
     }
-    fn to_string(&self) -> String {
-        String::from(self.name.lexeme)
+
+    pub fn bind(&self, instance: &Rc<LoxInstance>) -> LoxFunction {
+        let mut environment = Environment::new_enclosing(Rc::clone(&self.closure));
+        environment.define("this", Object::Instance(Rc::clone(instance)));
+
+        Self {
+            name: self.name.clone(),
+            params: Rc::clone(&self.params),
+            body: Rc::clone(&self.body),
+            closure: Rc::new(RefCell::new(environment)),
+            is_initializer: self.is_initializer
+        }
+    }
+
+    fn this(&self) -> Result<Object, LoxError> {
+        let this = Token::new(TokenType::THIS, String::from("this"), None, self.name.span);
+        self.closure.borrow().get_at(0, &this)
     }
 }
 
 impl LoxCallable for LoxFunction {
     fn call(&self, interpreter: &Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
-        let mut e = Environment::new_enclosing(Rc::clone(&interpreter.globals));
+        self.call_bound(interpreter, arguments.into_iter().map(Some).collect())
+    }
+
+    fn call_bound(&self, interpreter: &Interpreter, arguments: Vec<Option<Object>>) -> Result<Object, LoxError> {
+        let flow = self.call_frame(interpreter, arguments)?;
+        interpreter.run_tail_calls(flow)
+    }
+
+    // defaults are evaluated in order, so they can use the parameters before them;
+    // every parameter is defined, even past the end of arguments that weren't bound to the signature
+    fn call_frame(&self, interpreter: &Interpreter, arguments: Vec<Option<Object>>) -> Result<ControlFlow, LoxError> {
+        let e = Rc::new(RefCell::new(Environment::new_enclosing(Rc::clone(&self.closure))));
 
-        for (param, arg) in self.params.iter().zip(arguments.iter()) {
-            e.define(&param.lexeme, arg.clone())
+        let mut arguments = arguments.into_iter();
+        for param in self.params.iter() {
+            let value = match (arguments.next().flatten(), &param.default) {
+                (Some(value), _) => value,
+                (None, Some(default)) => interpreter.evaluate_in(default.clone(), Rc::clone(&e))?,
+                (None, None) if param.rest => Object::Array(Rc::new(RefCell::new(LoxArray::default()))),
+                (None, None) => Object::Nil
+            };
+            e.borrow_mut().define(&param.name.lexeme, value);
         }
 
-        interpreter.execute_block(&self.body, e)?;
-        Ok(Object::Nil)
-    }
-    fn arity(&self) -> usize{
-        self.params.len()
+        match interpreter.execute_block(&self.body, e)? {
+            _ if self.is_initializer => self.this().map(ControlFlow::Return),
+            flow @ (ControlFlow::Return(_) | ControlFlow::TailCall(_)) => Ok(flow),
+            _ => Ok(ControlFlow::Return(Object::Nil))
+        }
     }
-}
 
-pub struct NativeClock;
+    fn signature(&self) -> Signature {
+        Signature::from_parameters(&self.params)
+    }
 
-impl LoxCallable for NativeClock {
-    fn call(&self, _interpreter: &Interpreter, _arguments: Vec<Object>) ->  Result<Object, LoxError>{
-        match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
-            Ok(n) => Ok(Object::Number(n.as_millis() as f64)),
-            Err(e) => Err(LoxError::null()) //TODO: make error for clock
-        }
+    fn name(&self) -> String {
+        self.name.lexeme.clone()
     }
 
-    fn arity(&self) -> usize {
-        0
+    fn declaration(&self) -> Option<Label> {
+        Some(Label::new(self.name.span, "function declared here"))
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,79 @@
+use crate::object::*;
+use crate::token::*;
+use crate::loxclass::*;
+use crate::error::*;
+use crate::diagnostic::ErrorCode;
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+
+pub struct LoxInstance {
+    pub class: Rc<LoxClass>,
+    fields: RefCell<HashMap<String, Object>>
+}
+
+impl PartialEq for LoxInstance {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl fmt::Debug for LoxInstance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl fmt::Display for LoxInstance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} instance", self.class.name)
+    }
+}
+
+impl LoxInstance {
+    pub fn new(class: Rc<LoxClass>) -> Self {
+        LoxInstance {
+            class,
+            fields: RefCell::new(HashMap::new())
+        }
+    }
+
+    // fields shadow methods, and methods come back bound to this instance
+    pub fn get(instance: &Rc<LoxInstance>, name: &Token, via_this: bool) -> Result<Object, LoxError> {
+        instance.check_access(name, via_this)?;
+
+        if let Some(value) = instance.fields.borrow().get(&name.lexeme) {
+            return Ok(value.clone());
+        }
+
+        if let Some(method) = instance.class.find_method(&name.lexeme) {
+            return Ok(Object::Func(Rc::new(method.bind(instance))));
+        }
+
+        Err(LoxError::runtime_error(name, ErrorCode::UndefinedProperty, String::from(format!("Undefined property '{}'.", name.lexeme))))
+    }
+
+    pub fn set(&self, name: &Token, value: Object, via_this: bool) -> Result<(), LoxError> {
+        self.check_access(name, via_this)?;
+
+        if self.class.is_sealed() && !self.fields.borrow().contains_key(&name.lexeme) {
+            return Err(LoxError::runtime_error(name, ErrorCode::NotDynamic, String::from(format!("Can't add field '{}' to an instance of non-dynamic class '{}'.", name.lexeme, self.class.name))));
+        }
+
+        self.fields.borrow_mut().insert(name.lexeme.clone(), value);
+        Ok(())
+    }
+
+    pub fn define(&self, name: &str, value: Object) {
+        self.fields.borrow_mut().insert(name.to_string(), value);
+    }
+
+    fn check_access(&self, name: &Token, via_this: bool) -> Result<(), LoxError> {
+        if !via_this && self.class.is_private(&name.lexeme) {
+            return Err(LoxError::runtime_error(name, ErrorCode::PrivateMember, String::from(format!("Private member '{}' is only accessible through 'this'.", name.lexeme))));
+        }
+        Ok(())
+    }
+}
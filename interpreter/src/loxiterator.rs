@@ -0,0 +1,83 @@
+use crate::object::*;
+use crate::token::*;
+use crate::tokentype::TokenType;
+use crate::error::*;
+use crate::interpreter::*;
+use crate::loxcallable::*;
+use crate::loxinstance::*;
+use crate::loxarray::LoxArray;
+use crate::controlflow::BoundCall;
+use crate::diagnostic::{StackFrame, ErrorCode};
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+// what a for-in loop walks over
+// arrays are read as the loop goes, so elements the body appends are visited too;
+// strings and map keys are taken up front
+pub enum LoxIterator {
+    Values(std::vec::IntoIter<Object>),
+    Array(Rc<RefCell<LoxArray>>, usize),
+    // counting up in steps of 1 from the start, computed from the step count so no error builds up
+    Range(f64, f64, bool, usize),
+    // an object with hasNext() and next() methods
+    Instance(Rc<LoxInstance>),
+}
+
+impl LoxIterator {
+    // an instance is iterable if it has hasNext() and next(), or an iterator() method returning such an object
+    pub fn new(iterable: Object, interpreter: &Interpreter, keyword: &Token) -> Result<LoxIterator, LoxError> {
+        match iterable {
+            Object::String(s) => Ok(LoxIterator::Values(s.chars().map(|c| Object::String(c.to_string())).collect::<Vec<_>>().into_iter())),
+            Object::Array(array) => Ok(LoxIterator::Array(array, 0)),
+            Object::Map(map) => Ok(LoxIterator::Values(map.borrow().keys().iter().map(|key| key.to_object()).collect::<Vec<_>>().into_iter())),
+            Object::Range(start, end, inclusive) => Ok(LoxIterator::Range(start, end, inclusive, 0)),
+            Object::Instance(instance) if instance.class.find_method("iterator").is_some() => {
+                match call_method(&instance, "iterator", interpreter, keyword)? {
+                    Object::Instance(iterator) => Ok(LoxIterator::Instance(iterator)),
+                    _ => Err(LoxError::runtime_error(keyword, ErrorCode::NotIterable, String::from("iterator() must return an instance.")))
+                }
+            }
+            Object::Instance(instance) => Ok(LoxIterator::Instance(instance)),
+            _ => Err(LoxError::runtime_error(keyword, ErrorCode::NotIterable, String::from("Can only iterate over strings, arrays, maps, ranges and iterable instances.")))
+        }
+    }
+
+    pub fn next(&mut self, interpreter: &Interpreter, keyword: &Token) -> Result<Option<Object>, LoxError> {
+        match self {
+            LoxIterator::Values(values) => Ok(values.next()),
+            LoxIterator::Array(array, index) => {
+                let element = array.borrow().get(*index).cloned();
+                *index += 1;
+                Ok(element)
+            }
+            LoxIterator::Range(start, end, inclusive, steps) => {
+                let value = *start + *steps as f64;
+                if value < *end || *inclusive && value == *end {
+                    *steps += 1;
+                    Ok(Some(Object::Number(value)))
+                } else {
+                    Ok(None)
+                }
+            }
+            LoxIterator::Instance(iterator) => {
+                if !interpreter.is_truthy(&call_method(iterator, "hasNext", interpreter, keyword)?) {
+                    return Ok(None);
+                }
+                call_method(iterator, "next", interpreter, keyword).map(Some)
+            }
+        }
+    }
+}
+
+fn call_method(instance: &Rc<LoxInstance>, name: &str, interpreter: &Interpreter, keyword: &Token) -> Result<Object, LoxError> {
+    let name = Token::new(TokenType::IDENTIFIER, name.to_string(), None, keyword.span);
+    match LoxInstance::get(instance, &name, true)? {
+        Object::Func(method) if method.signature().accepts(0) => {
+            let arguments = method.signature().bind(Vec::new(), Vec::new(), keyword)?;
+            let frame = StackFrame{function: method.name(), call_site: keyword.span, native: false};
+            interpreter.call(BoundCall{function: method, arguments, frame}, keyword)
+        }
+        _ => Err(LoxError::runtime_error(keyword, ErrorCode::NotIterable, String::from(format!("'{}' must be a method that takes no arguments.", name.lexeme))))
+    }
+}
@@ -0,0 +1,107 @@
+use crate::object::*;
+
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    String(String),
+    // bits of the number, with -0 folded into 0 so both find the same entry
+    Number(u64),
+    Bool(bool),
+}
+
+impl MapKey {
+    pub fn from_object(object: &Object) -> Result<MapKey, String> {
+        match object {
+            Object::String(s) => Ok(MapKey::String(s.clone())),
+            Object::Number(n) if n.is_nan() => Err(String::from("Map key can't be NaN.")),
+            Object::Number(n) => Ok(MapKey::Number(if *n == 0.0 { 0.0f64.to_bits() } else { n.to_bits() })),
+            Object::Bool(b) => Ok(MapKey::Bool(*b)),
+            _ => Err(String::from("Map keys must be strings, numbers or booleans.")),
+        }
+    }
+
+    pub fn to_object(&self) -> Object {
+        match self {
+            MapKey::String(s) => Object::String(s.clone()),
+            MapKey::Number(bits) => Object::Number(f64::from_bits(*bits)),
+            MapKey::Bool(b) => Object::Bool(*b),
+        }
+    }
+}
+
+impl fmt::Display for MapKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapKey::String(s) => write!(f, "{s:?}"),
+            _ => write!(f, "{}", self.to_object()),
+        }
+    }
+}
+
+// entries keep insertion order so printing and iterating a map is deterministic
+#[derive(Default, PartialEq)]
+pub struct LoxMap {
+    entries: Vec<(MapKey, Object)>,
+    index: HashMap<MapKey, usize>,
+}
+
+impl LoxMap {
+    pub fn new() -> Self {
+        LoxMap::default()
+    }
+
+    pub fn get(&self, key: &MapKey) -> Option<Object> {
+        self.index.get(key).map(|i| self.entries[*i].1.clone())
+    }
+
+    pub fn contains(&self, key: &MapKey) -> bool {
+        self.index.contains_key(key)
+    }
+
+    // overwriting an existing key keeps its original position
+    pub fn insert(&mut self, key: MapKey, value: Object) {
+        match self.index.get(&key) {
+            Some(i) => self.entries[*i].1 = value,
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &MapKey) -> Option<Object> {
+        let i = self.index.remove(key)?;
+        let (_, value) = self.entries.remove(i);
+        for (key, _) in self.entries[i..].iter() {
+            if let Some(position) = self.index.get_mut(key) {
+                *position -= 1;
+            }
+        }
+        Some(value)
+    }
+
+    pub fn keys(&self) -> Vec<MapKey> {
+        self.entries.iter().map(|(key, _)| key.clone()).collect()
+    }
+
+    pub fn entries(&self) -> &[(MapKey, Object)] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn take_values(&mut self) -> Vec<Object> {
+        self.index.clear();
+        self.entries.drain(..).map(|(_, value)| value).collect()
+    }
+}
+
+impl Drop for LoxMap {
+    fn drop(&mut self) {
+        drop_contents(self.take_values());
+    }
+}
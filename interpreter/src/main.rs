@@ -1,40 +1,110 @@
+#![allow(clippy::needless_return, clippy::redundant_field_names, clippy::needless_question_mark, clippy::useless_conversion, clippy::result_large_err)]
+
 mod lox;
 
 use std::env::args;
+use std::thread;
 
 use crate::lox::Lox;
+use crate::diagnostic::ErrorFormat;
+use crate::interpreter::DEFAULT_MAX_CALL_DEPTH;
 
 //mod astprinter;
 
 mod tokentype;
 mod token;
+mod span;
 mod object;
 mod scanner;
 mod error;
+mod controlflow;
+mod diagnostic;
 mod expr;
 mod stmt;
+mod pattern;
+mod signature;
 mod parser;
 mod interpreter;
+mod resolver;
 mod environment;
 mod loxcallable;
 mod loxfunction;
 mod nativefunction;
+mod loxclass;
+mod loxinstance;
+mod loxmap;
+mod loxarray;
+mod loxiterator;
+
 
+// native stack reserved per Lox call, with room for deep expressions inside each call in debug builds
+const STACK_PER_CALL: usize = 64 * 1024;
+// enough to parse and run code nested as deeply as the parser allows, in a debug build
+const MIN_STACK: usize = 32 * 1024 * 1024;
+// a bigger --max-call-depth than this covers gets a stack overflow error instead of more memory
+const MAX_STACK: usize = 512 * 1024 * 1024;
 
 fn main() {
-    let args: Vec<String> = args().collect();
-    let mut lox: Lox = Lox::new();
-
-    if args.len() < 1 {
-        println!("Usage: rustlox [script]");
-        std::process::exit(64);
-    } 
-    else if args.len() == 2 {
-        lox.run_file(&args[1]).expect("Could not run file");
+    let mut args: Vec<String> = args().collect();
+
+    let mut error_format = ErrorFormat::Human;
+    if let Some(i) = args.iter().position(|arg| arg.starts_with("--error-format")) {
+        error_format = match args.remove(i).as_str() {
+            "--error-format=human" => ErrorFormat::Human,
+            "--error-format=json" => ErrorFormat::Json,
+            _ => usage()
+        };
     }
-    else {
-        lox.run_prompt();
+
+    let mut max_call_depth = DEFAULT_MAX_CALL_DEPTH;
+    let depth_flag = args.iter().position(|arg| arg.starts_with("--max-call-depth"));
+    if let Some(i) = depth_flag {
+        max_call_depth = match args.remove(i).strip_prefix("--max-call-depth=").map(str::parse) {
+            Some(Ok(depth)) => depth,
+            _ => usage()
+        };
+    }
+
+    if args.len() > 2 {
+        usage();
+    }
+
+    // the interpreter recurses for every Lox call, so it gets a stack big enough for the deepest one allowed;
+    // calls that outgrow it anyway stop with a stack overflow error
+    let stack_size = max_call_depth.saturating_mul(STACK_PER_CALL).clamp(MIN_STACK, MAX_STACK);
+    let interpreter = thread::Builder::new()
+        .stack_size(stack_size)
+        .spawn(move || {
+            let mut lox: Lox = Lox::new(error_format);
+            lox.set_max_call_depth(max_call_depth);
+            lox.set_stack_size(stack_size);
+
+            if args.len() == 2 {
+                lox.run_file(&args[1]);
+            }
+            else {
+                lox.run_prompt();
+            }
+        });
+
+    let interpreter = match interpreter {
+        Ok(handle) => handle,
+        Err(e) => {
+            eprintln!("Could not start the interpreter with a {} MiB stack: {e}.", stack_size / (1024 * 1024));
+            // only the flag asks for more than the default stack
+            std::process::exit(if depth_flag.is_some() { 64 } else { 70 });
+        }
+    };
+
+    // a panic has already printed its message
+    if interpreter.join().is_err() {
+        std::process::exit(70);
     }
 }
 
 
+
+fn usage() -> ! {
+    println!("Usage: rustlox [--error-format=human|json] [--max-call-depth=N] [script]");
+    std::process::exit(64);
+}
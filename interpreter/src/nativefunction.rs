@@ -1,12 +1,17 @@
   
 use std::fmt;
 use std::rc::Rc;
+use std::cell::RefCell;
 use std::time::SystemTime;
 
 use crate::loxcallable::*;
 use crate::interpreter::*;
 use crate::object::*;
 use crate::error::*;
+use crate::diagnostic::ErrorCode;
+use crate::loxmap::*;
+use crate::loxarray::*;
+use crate::signature::Signature;
 
 #[derive(Clone)]
 pub struct LoxNative {
@@ -37,14 +42,105 @@ impl fmt::Display for LoxNative {
 pub struct NativeClock;
 
 impl LoxCallable for NativeClock {
-    fn call(&self, interpreter: &Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+    fn call(&self, _interpreter: &Interpreter, _arguments: Vec<Object>) -> Result<Object, LoxError> {
         match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
             Ok(n) => Ok(Object::Number(n.as_millis() as f64)),
-            Err(e) => Err(LoxError::null())//TODO: finish clock error,
+            Err(_e) => Err(LoxError::native_error("clock", ErrorCode::ClockUnavailable, String::from("System time is before the Unix epoch.")))
         }
     }
 
-    fn arity(&self) -> usize {
-        0
+    fn signature(&self) -> Signature {
+        Signature::new(&[])
     }
-}
\ No newline at end of file
+
+    fn name(&self) -> String {
+        String::from("clock")
+    }
+}
+
+pub struct NativeLen;
+
+impl LoxCallable for NativeLen {
+    fn call(&self, _interpreter: &Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        match &arguments[0] {
+            Object::String(s) => Ok(Object::Number(s.chars().count() as f64)),
+            Object::Array(array) => Ok(Object::Number(array.borrow().len() as f64)),
+            Object::Map(map) => Ok(Object::Number(map.borrow().len() as f64)),
+            _ => Err(LoxError::native_error("len", ErrorCode::InvalidArgument, String::from("Argument must be a string, array or map.")))
+        }
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(&["value"])
+    }
+
+    fn name(&self) -> String {
+        String::from("len")
+    }
+}
+
+pub struct NativeKeys;
+
+impl LoxCallable for NativeKeys {
+    fn call(&self, _interpreter: &Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        match &arguments[0] {
+            Object::Map(map) => {
+                let keys: Vec<Object> = map.borrow().keys().iter().map(|key| key.to_object()).collect();
+                Ok(Object::Array(Rc::new(RefCell::new(LoxArray::from(keys)))))
+            }
+            _ => Err(LoxError::native_error("keys", ErrorCode::InvalidArgument, String::from("Argument must be a map.")))
+        }
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(&["map"])
+    }
+
+    fn name(&self) -> String {
+        String::from("keys")
+    }
+}
+
+pub struct NativeHas;
+
+impl LoxCallable for NativeHas {
+    fn call(&self, _interpreter: &Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        match &arguments[0] {
+            Object::Map(map) => {
+                let key = MapKey::from_object(&arguments[1]).map_err(|message| LoxError::native_error("has", ErrorCode::InvalidMapKey, message))?;
+                Ok(Object::Bool(map.borrow().contains(&key)))
+            }
+            _ => Err(LoxError::native_error("has", ErrorCode::InvalidArgument, String::from("First argument must be a map.")))
+        }
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(&["map", "key"])
+    }
+
+    fn name(&self) -> String {
+        String::from("has")
+    }
+}
+
+pub struct NativeRemove;
+
+impl LoxCallable for NativeRemove {
+    fn call(&self, _interpreter: &Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        match &arguments[0] {
+            Object::Map(map) => {
+                let key = MapKey::from_object(&arguments[1]).map_err(|message| LoxError::native_error("remove", ErrorCode::InvalidMapKey, message))?;
+                Ok(map.borrow_mut().remove(&key).unwrap_or(Object::Nil))
+            }
+            _ => Err(LoxError::native_error("remove", ErrorCode::InvalidArgument, String::from("First argument must be a map.")))
+        }
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new(&["map", "key"])
+    }
+
+    fn name(&self) -> String {
+        String::from("remove")
+    }
+}
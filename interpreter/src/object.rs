@@ -1,21 +1,119 @@
-
 use std::fmt;
 use std::rc::Rc;
+use std::cell::RefCell;
 
 use crate::loxfunction::*;
 use crate::nativefunction::*;
+use crate::loxclass::*;
+use crate::loxinstance::*;
+use crate::loxmap::*;
+use crate::loxarray::*;
 
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub enum Object {
     String(String),
     Number(f64),
     Bool(bool),
     Func(Rc<LoxFunction>),
     Native(Rc<LoxNative>),
+    Class(Rc<LoxClass>),
+    Instance(Rc<LoxInstance>),
+    Array(Rc<RefCell<LoxArray>>),
+    Map(Rc<RefCell<LoxMap>>),
+    // start, end, and whether the end is included
+    Range(f64, f64, bool),
     Nil,
 }
 
+// deeper containers print as [...] or #{...}, so printing doesn't run out of native stack
+const MAX_PRINT_DEPTH: usize = 100;
+
+impl Object {
+    // strings nested in a container are quoted so their boundaries stay visible
+    fn fmt_element(&self, f: &mut fmt::Formatter, seen: &mut Vec<*const ()>, debug: bool) -> fmt::Result {
+        match self {
+            Object::String(x) if !debug => write!(f, "{x:?}"),
+            _ => self.fmt_nested(f, seen, debug),
+        }
+    }
+
+    // containers can contain themselves, so nested values remember what is already being printed
+    fn fmt_nested(&self, f: &mut fmt::Formatter, seen: &mut Vec<*const ()>, debug: bool) -> fmt::Result {
+        match self {
+            Object::Map(map) => {
+                let ptr = Rc::as_ptr(map) as *const ();
+                if seen.contains(&ptr) || seen.len() == MAX_PRINT_DEPTH {
+                    return write!(f, "#{{...}}");
+                }
+                seen.push(ptr);
+
+                if debug {
+                    write!(f, "Map(")?;
+                }
+                write!(f, "#{{")?;
+                for (i, (key, value)) in map.borrow().entries().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{key}: ")?;
+                    value.fmt_element(f, seen, debug)?;
+                }
+                write!(f, "}}")?;
+                if debug {
+                    write!(f, ")")?;
+                }
+
+                seen.pop();
+                Ok(())
+            }
+            Object::Array(array) => {
+                let ptr = Rc::as_ptr(array) as *const ();
+                if seen.contains(&ptr) || seen.len() == MAX_PRINT_DEPTH {
+                    return write!(f, "[...]");
+                }
+                seen.push(ptr);
+
+                if debug {
+                    write!(f, "Array(")?;
+                }
+                write!(f, "[")?;
+                for (i, element) in array.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    element.fmt_element(f, seen, debug)?;
+                }
+                write!(f, "]")?;
+                if debug {
+                    write!(f, ")")?;
+                }
+
+                seen.pop();
+                Ok(())
+            }
+            _ if debug => match self {
+                Object::String(x) => write!(f, "String({x:?})"),
+                Object::Number(x) => write!(f, "Number({x:?})"),
+                Object::Bool(x) => write!(f, "Bool({x:?})"),
+                Object::Func(function) => write!(f, "Func({function:?})"),
+                Object::Native(function) => write!(f, "Native({function:?})"),
+                Object::Class(class) => write!(f, "Class({class:?})"),
+                Object::Instance(instance) => write!(f, "Instance({instance:?})"),
+                Object::Range(..) => write!(f, "Range({self})"),
+                _ => write!(f, "Nil"),
+            }
+            _ => write!(f, "{self}"),
+        }
+    }
+}
+
+impl fmt::Debug for Object {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_nested(f, &mut Vec::new(), true)
+    }
+}
+
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -31,6 +129,30 @@ impl fmt::Display for Object {
             Object::Nil => write!(f, "nil"),
             Object::Func(function) => write!(f, "{function}"),
             Object::Native(function) => write!(f, "{function}"),
+            Object::Class(class) => write!(f, "{class}"),
+            Object::Instance(instance) => write!(f, "{instance}"),
+            Object::Range(start, end, inclusive) => write!(f, "{start}{}{end}", if *inclusive { "..=" } else { ".." }),
+            Object::Array(_) | Object::Map(_) => self.fmt_nested(f, &mut Vec::new(), false),
         }
     }
-}
\ No newline at end of file
+}
+
+// dropping containers nested one inside the next would recurse once per level, so the contents of
+// each array or map dropped for the last time go onto a worklist instead
+pub fn drop_contents(mut pending: Vec<Object>) {
+    while let Some(object) = pending.pop() {
+        match object {
+            Object::Array(array) => {
+                if let Ok(array) = Rc::try_unwrap(array) {
+                    pending.append(&mut array.into_inner());
+                }
+            }
+            Object::Map(map) => {
+                if let Ok(map) = Rc::try_unwrap(map) {
+                    pending.append(&mut map.into_inner().take_values());
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -5,21 +5,36 @@ use crate::tokentype::TokenType;
 use crate::expr::Expr;
 use crate::object::Object;
 use crate::error::LoxError;
+use crate::diagnostic::ErrorCode;
 use crate::expr::*;
 use crate::stmt::Stmt;
 use crate::stmt::*;
 use crate::token::Token;
+use crate::span::Span;
+use crate::pattern::*;
+use crate::signature::*;
 
 
 use std::rc::Rc;
 
+// deeper nesting than this is refused, so nothing that walks the tree can overflow the native stack
+const MAX_NESTING: usize = 256;
 
 #[derive(Debug, Clone)]
 pub struct Parser {
     pub tokens: Vec<Token>,
     current: usize,
-    had_error: bool,
-    loop_depth: usize
+    // errors that don't stop the parse, like too many arguments
+    errors: Vec<LoxError>,
+    loop_depth: usize,
+    // labels of the loops around the current statement, reset at each function body
+    loop_labels: Vec<String>,
+    // statements and expressions being parsed inside one another
+    nesting: usize,
+    // set while unwinding out of a top-level declaration that nests too deeply, so nothing on the way is reported
+    abandoned: bool,
+    // where the top-level declaration being parsed begins
+    declaration_start: usize
 }
 
 impl Parser {
@@ -27,50 +42,147 @@ impl Parser {
         Parser {
             tokens,
             current: 0,
-            had_error: false,
-            loop_depth: 0
+            errors: Vec::new(),
+            loop_depth: 0,
+            loop_labels: Vec::new(),
+            nesting: 0,
+            abandoned: false,
+            declaration_start: 0
         }
     }
 
-    pub fn parse(&mut self) -> Result<Vec<Rc<Stmt>>, LoxError>{
+    pub fn parse(&mut self) -> Result<Vec<Rc<Stmt>>, Vec<LoxError>>{
         let mut statements : Vec<Rc<Stmt>>= Vec::new();
         while !self.is_at_end() {
-            statements.push(self.declaration()?)
+            self.declaration_start = self.current;
+            if let Some(statement) = self.declaration() {
+                statements.push(statement);
+            }
+            self.abandoned = false;
         }
-        if self.had_error {
-            Err(LoxError::null())
+        if !self.errors.is_empty() {
+            Err(std::mem::take(&mut self.errors))
         } else {
             return Ok(statements);
         }
     }
 
     fn expression(&mut self) -> Result<Expr, LoxError>{
-        Ok(self.assignment()?)
+        self.nested(Self::assignment)
     }
 
-    fn declaration(&mut self) -> Result<Rc<Stmt>, LoxError>{
+    // a declaration that fails to parse is recorded, skipped and left out of the tree
+    fn declaration(&mut self) -> Option<Rc<Stmt>>{
         let result = 
-        if self.is_match(&[VAR]) {
-            return Ok(self.var_declaration()?)
+        if self.is_match(&[CLASS]) {
+            self.nested(|parser| parser.class_declaration(Vec::new()))
+        }
+        else if self.check_modifier() {
+            let modifiers = self.modifiers();
+            self.consume(CLASS, String::from("Expect 'class' after class modifiers."))
+                .and_then(|_| self.nested(|parser| parser.class_declaration(modifiers)))
         }
-        else if self.is_match(&[FUN]) {
-            return Ok(self.function("function")?)
+        else if self.is_match(&[VAR]) {
+            self.var_declaration(Vec::new()).map(|var| Rc::new(Stmt::Var(var)))
+        }
+        // 'fun (' starts an anonymous function, which is an expression
+        else if self.check(FUN) && !self.check_next(LEFT_PAREN) {
+            self.advance();
+            self.nested(|parser| parser.function("function", Vec::new())).map(|function| Rc::new(Stmt::Function(function)))
         }
         else {
-            Ok(self.statement()?)
+            self.statement()
         };
 
-        if result.is_err() {
-            self.synchronize();
+        match result {
+            Ok(statement) => Some(statement),
+            Err(_) if self.abandoned => None,
+            Err(e) => {
+                self.report(e);
+                self.synchronize();
+                None
+            }
         }
+    }
 
-        result
+    fn report(&mut self, error: LoxError) {
+        // the scanner has already reported whatever an ERROR token stands for
+        if !matches!(&error, LoxError::ParseError {token, ..} if token.t_type == ERROR) {
+            self.errors.push(error);
+        }
+    }
+
+    fn class_declaration(&mut self, modifiers: Vec<Token>) -> Result<Rc<Stmt>, LoxError> {
+        let start = self.start_span(&modifiers);
+        let name = self.consume(IDENTIFIER, String::from("Expect class name."))?;
+
+        let superclass = 
+            if self.is_match(&[LESS]) {
+                let name = self.consume(IDENTIFIER, String::from("Expect superclass name."))?;
+                Some(Rc::new(Expr::Variable(Rc::new(VariableExpr{span: name.span, name}))))
+            } else {
+                None
+            };
+
+        self.consume(LEFT_BRACE, String::from("Expect '{' before class body."))?;
+
+        let body_start = self.current;
+        let mut methods = Vec::new();
+        let mut fields = Vec::new();
+        while !self.check(RIGHT_BRACE) && !self.is_at_end() {
+            let member_start = self.current;
+            let member_modifiers = self.modifiers();
+            let member =
+                if self.is_match(&[VAR]) {
+                    self.var_declaration(member_modifiers).map(|field| fields.push(field))
+                } else {
+                    // 'fun' is optional in front of a method
+                    self.is_match(&[FUN]);
+                    self.function("method", member_modifiers).map(|method| methods.push(method))
+                };
+
+            // a broken member is reported and skipped so the rest of the class still parses
+            if let Err(e) = member {
+                if self.abandoned {
+                    return Err(e);
+                }
+                if self.current == member_start {
+                    self.advance();
+                }
+                self.synchronize_member(body_start);
+                if self.is_at_end() {
+                    return Err(e);
+                }
+                self.report(e);
+            }
+        }
+
+        self.consume(RIGHT_BRACE, String::from("Expect '}' after class body."))?;
+
+        Ok(Rc::new(Stmt::Class(Rc::new(ClassStmt{name, superclass, modifiers, methods, fields, span: self.span_from(start)}))))
+    }
+
+    fn check_modifier(&self) -> bool {
+        matches!(self.peek().t_type, PRIVATE | PUBLIC | DYNAMIC | STATIC | OVERRIDE)
+    }
+
+    fn modifiers(&mut self) -> Vec<Token> {
+        let mut modifiers = Vec::new();
+        while self.check_modifier() {
+            modifiers.push(self.advance());
+        }
+        modifiers
     }
 
     fn statement(&mut self) -> Result<Rc<Stmt>, LoxError> {
-        
+        self.nested(Self::any_statement)
+    }
+
+    fn any_statement(&mut self) -> Result<Rc<Stmt>, LoxError> {
         if self.is_match(&[LEFT_BRACE]) {
-            return Ok(Rc::new(Stmt::Block(Rc::new(BlockStmt{statements: Rc::new(self.block()?)}))));
+            let start = self.previous().span;
+            let statements = Rc::new(self.block()?);
+            return Ok(Rc::new(Stmt::Block(Rc::new(BlockStmt{statements, span: self.span_from(start)}))));
         }
         if self.is_match(&[PRINT]) {
             return Ok(Rc::new(self.print_statement()?));
@@ -78,11 +190,20 @@ impl Parser {
         if self.is_match(&[IF]) {
             return Ok(Rc::new(self.if_statement()?));
         }
+        if self.check(IDENTIFIER) && self.check_next(COLON) {
+            return self.labeled_statement();
+        }
         if self.is_match(&[WHILE]) {
-            return Ok(Rc::new(self.while_statement()?));
+            return Ok(Rc::new(self.while_statement(None)?));
         }
         if self.is_match(&[FOR]) {
-            return Ok(self.for_statement()?);
+            return Ok(self.for_statement(None)?);
+        }
+        if self.is_match(&[DO]) {
+            return Ok(Rc::new(self.do_statement(None)?));
+        }
+        if self.is_match(&[LOOP]) {
+            return Ok(Rc::new(self.loop_statement(None)?));
         }
         if self.is_match(&[BREAK]) {
             return Ok(Rc::new(self.break_statement()?));
@@ -90,33 +211,188 @@ impl Parser {
         if self.is_match(&[CONTINUE]) {
             return Ok(Rc::new(self.continue_statement()?));
         }
+        if self.is_match(&[RETURN]) {
+            return Ok(Rc::new(self.return_statement()?));
+        }
+        if self.is_match(&[MATCH]) {
+            return Ok(Rc::new(self.match_statement()?));
+        }
          
         return Ok(self.expression_statement()?);
     }
 
+    fn labeled_statement(&mut self) -> Result<Rc<Stmt>, LoxError> {
+        let label = self.advance();
+        self.advance();
+        if self.loop_labels.contains(&label.lexeme) {
+            let err = self.error(label.clone(), ErrorCode::DuplicateLabel, String::from(format!("Label '{}' is already used by an enclosing loop.", label.lexeme)));
+            self.errors.push(err);
+        }
+
+        self.loop_labels.push(label.lexeme.clone());
+        let statement =
+            if self.is_match(&[WHILE]) {
+                self.while_statement(Some(label)).map(Rc::new)
+            } else if self.is_match(&[FOR]) {
+                self.for_statement(Some(label))
+            } else if self.is_match(&[DO]) {
+                self.do_statement(Some(label)).map(Rc::new)
+            } else if self.is_match(&[LOOP]) {
+                self.loop_statement(Some(label)).map(Rc::new)
+            } else {
+                let peek_var = self.peek();
+                Err(self.error(peek_var, ErrorCode::ExpectedToken, String::from("Expect loop after label.")))
+            };
+        self.loop_labels.pop();
+        statement
+    }
+
+    // the label of a break or continue has to name one of the enclosing loops
+    fn loop_label(&mut self) -> Option<Token> {
+        if !self.is_match(&[IDENTIFIER]) {
+            return None;
+        }
+
+        let label = self.previous();
+        if !self.loop_labels.contains(&label.lexeme) {
+            let err = self.error(label.clone(), ErrorCode::UndefinedLabel, String::from(format!("No enclosing loop is labeled '{}'.", label.lexeme)));
+            self.errors.push(err);
+        }
+        Some(label)
+    }
+
     fn break_statement(&mut self) -> Result<Stmt, LoxError> {
+        let token = self.previous();
+        let label = self.loop_label();
         self.consume(SEMICOLON, String::from("Expect ';' after 'break'."))?;
-        Ok(Stmt::Break(Rc::new(BreakStmt{token: Token::new(BREAK, String::from(""), Some(Object::Nil), 0)})))
+        Ok(Stmt::Break(Rc::new(BreakStmt{span: self.span_from(token.span), token, label})))
     }
 
     fn continue_statement(&mut self) -> Result<Stmt, LoxError> {
+        let token = self.previous();
+        let label = self.loop_label();
         self.consume(SEMICOLON, String::from("Expect ';' after 'continue'."))?;
-        Ok(Stmt::Continue(Rc::new(ContinueStmt{token: Token::new(CONTINUE, String::from(""), Some(Object::Nil), 0)})))
+        Ok(Stmt::Continue(Rc::new(ContinueStmt{span: self.span_from(token.span), token, label})))
     }
 
-    fn for_statement(&mut self) -> Result<Rc<Stmt>, LoxError> {
+    fn match_statement(&mut self) -> Result<Stmt, LoxError> {
+        let (keyword, subject) = self.match_subject()?;
+        let mut arms = Vec::new();
+
+        while !self.check(RIGHT_BRACE) && !self.is_at_end() {
+            let patterns = self.match_patterns()?;
+            let body = self.statement()?;
+            arms.push(MatchArm{patterns, body});
+            self.is_match(&[COMMA]);
+        }
+
+        self.consume(RIGHT_BRACE, String::from("Expect '}' after match arms."))?;
+        Ok(Stmt::Match(Rc::new(MatchStmt{span: self.span_from(keyword.span), keyword, subject: Rc::new(subject), arms})))
+    }
+
+    fn match_expression(&mut self) -> Result<Expr, LoxError> {
+        let (keyword, subject) = self.match_subject()?;
+        let mut arms = Vec::new();
+
+        while !self.check(RIGHT_BRACE) && !self.is_at_end() {
+            let patterns = self.match_patterns()?;
+            let body = self.expression()?;
+            arms.push(MatchArm{patterns, body: Rc::new(body)});
+            if !self.is_match(&[COMMA]) {
+                break;
+            }
+        }
+
+        self.consume(RIGHT_BRACE, String::from("Expect '}' after match arms."))?;
+        Ok(Expr::Match(Rc::new(MatchExpr{span: self.span_from(keyword.span), keyword, subject: Rc::new(subject), arms})))
+    }
+
+    fn match_subject(&mut self) -> Result<(Token, Expr), LoxError> {
+        let keyword = self.previous();
+        self.consume(LEFT_PAREN, String::from("Expect '(' after 'match'."))?;
+        let subject = self.expression()?;
+        self.consume(RIGHT_PAREN, String::from("Expect ')' after match value."))?;
+        self.consume(LEFT_BRACE, String::from("Expect '{' before match arms."))?;
+        Ok((keyword, subject))
+    }
+
+    fn match_patterns(&mut self) -> Result<Vec<Pattern>, LoxError> {
+        let mut patterns = vec![self.pattern()?];
+        while self.is_match(&[COMMA]) {
+            patterns.push(self.pattern()?);
+        }
+        self.consume(EQUAL_GREATER, String::from("Expect '=>' after match patterns."))?;
+        Ok(patterns)
+    }
+
+    fn pattern(&mut self) -> Result<Pattern, LoxError> {
+        if self.check(IDENTIFIER) && self.peek().lexeme == "_" {
+            self.advance();
+            return Ok(Pattern::Wildcard);
+        }
+        if self.is_match(&[TRUE]) {
+            return Ok(Pattern::Literal(Object::Bool(true)));
+        }
+        if self.is_match(&[FALSE]) {
+            return Ok(Pattern::Literal(Object::Bool(false)));
+        }
+        if self.is_match(&[NIL]) {
+            return Ok(Pattern::Literal(Object::Nil));
+        }
+        if self.is_match(&[STRING]) {
+            return Ok(Pattern::Literal(self.previous().literal.unwrap_or(Object::Nil)));
+        }
+
+        let from = self.pattern_number()?;
+        if self.is_match(&[DOT_DOT, DOT_DOT_EQUAL]) {
+            let inclusive = self.previous().t_type == DOT_DOT_EQUAL;
+            let to = self.pattern_number()?;
+            return Ok(Pattern::Range(from, to, inclusive));
+        }
+        Ok(Pattern::Literal(Object::Number(from)))
+    }
+
+    fn pattern_number(&mut self) -> Result<f64, LoxError> {
+        let negative = self.is_match(&[MINUS]);
+        let number = self.consume(NUMBER, String::from("Expect pattern."))?;
+        match number.literal {
+            Some(Object::Number(n)) if negative => Ok(-n),
+            Some(Object::Number(n)) => Ok(n),
+            _ => Err(self.error(number, ErrorCode::ExpectedToken, String::from("Expect pattern.")))
+        }
+    }
+
+    fn return_statement(&mut self) -> Result<Stmt, LoxError> {
+        let keyword = self.previous();
+        let value = 
+            if !self.check(SEMICOLON) {
+                Some(Rc::new(self.expression()?))
+            } else {
+                None
+            };
+
+        self.consume(SEMICOLON, String::from("Expect ';' after return value."))?;
+        Ok(Stmt::Return(Rc::new(ReturnStmt{span: self.span_from(keyword.span), keyword, value})))
+    }
+
+    // the loop is desugared into a while, and every node it produces covers the whole for statement
+    fn for_statement(&mut self, label: Option<Token>) -> Result<Rc<Stmt>, LoxError> {
+        let start = self.previous().span;
         self.consume(LEFT_PAREN, String::from("Expect '(' after 'for'."))?;
+        if self.check(VAR) && self.check_next(IDENTIFIER) && self.tokens.get(self.current + 2).is_some_and(|token| token.t_type == IN) {
+            return self.for_in_statement(start, label);
+        }
 
         let initializer = 
             if self.is_match(&[SEMICOLON]) {
                 None
             } else if self.is_match(&[VAR]) {
-                Some(self.var_declaration()?)
+                Some(Rc::new(Stmt::Var(self.var_declaration(Vec::new())?)))
             } else {
                 Some(self.expression_statement()?)
             };
         
-        let mut condition = 
+        let condition = 
             if !self.check(SEMICOLON) {
                 Some(self.expression()?)
             } else {
@@ -137,38 +413,28 @@ impl Parser {
         let try_body = | | -> Result<Rc<Stmt>, LoxError> {
             self.loop_depth += 1;
             let mut body = self.statement()?;
-            
-            match increment {
-                None => {},
-                Some(inc) => {
-                    body = Rc::new(Stmt::Block(Rc::new(BlockStmt{
-                        statements: Rc::new(vec!(body, Rc::new(Stmt::Expression(Rc::new(ExpressionStmt{
-                            expression: Rc::new(inc)
-                        })))))
-                    })))
-                }
-            }
+            let span = self.span_from(start);
 
-            match condition {
-                None => {
-                    condition = Some(Expr::Literal(Rc::new(LiteralExpr{
-                        value: Some(Object::Bool(false))
-                    })))
-                }
-                _ => {}
-            }
+            // a missing condition loops forever
+            let condition = condition.unwrap_or_else(|| Expr::Literal(Rc::new(LiteralExpr{
+                value: Some(Object::Bool(true)),
+                span
+            })));
 
             body = Rc::new(Stmt::While(Rc::new(WhileStmt{
-                condition: Rc::new(condition.unwrap()),
+                condition: Rc::new(condition),
                 body,
-                is_for_loop: true
+                increment: increment.map(Rc::new),
+                label,
+                span
             })));
 
             match initializer {
                 None => {},
                 Some(initializer) => {
                     body = Rc::new(Stmt::Block(Rc::new(BlockStmt{
-                        statements: Rc::new(vec!(initializer, body))
+                        statements: Rc::new(vec!(initializer, body)),
+                        span
                     })))
                 }
             }
@@ -180,7 +446,26 @@ impl Parser {
         Ok(body.clone())
     }
 
+    fn for_in_statement(&mut self, start: Span, label: Option<Token>) -> Result<Rc<Stmt>, LoxError> {
+        self.advance();
+        let name = self.advance();
+        let keyword = self.advance();
+        let iterable = self.expression()?;
+        self.consume(RIGHT_PAREN, String::from("Expect ')' after for-in clause."))?;
+        let body = self.statement()?;
+
+        Ok(Rc::new(Stmt::ForIn(Rc::new(ForInStmt{
+            name,
+            keyword,
+            iterable: Rc::new(iterable),
+            body,
+            label,
+            span: self.span_from(start)
+        }))))
+    }
+
     fn if_statement(&mut self) -> Result<Stmt, LoxError>{
+        let start = self.previous().span;
         self.consume(LEFT_PAREN, String::from("Expect '(' after 'if'."))?;
         let condition = self.expression();
 
@@ -198,18 +483,21 @@ impl Parser {
         Ok(Stmt::If(Rc::new(IfStmt{
             condition: Rc::new(condition?), 
             then_branch: then_branch?, 
-            else_branch: else_branch})))
+            else_branch: else_branch,
+            span: self.span_from(start)})))
     }
 
     fn print_statement(&mut self) -> Result<Stmt, LoxError> {
-        let value = self.expression();
+        let start = self.previous().span;
+        let value = self.expression()?;
 
         self.consume(SEMICOLON, String::from("Expect ';' after value."))?;
 
-        return Ok(Stmt::Print(Rc::new(PrintStmt {expression: Rc::new(value?)})));
+        return Ok(Stmt::Print(Rc::new(PrintStmt {expression: Rc::new(value), span: self.span_from(start)})));
     }
     
-    fn while_statement(&mut self) -> Result<Stmt, LoxError> {
+    fn while_statement(&mut self, label: Option<Token>) -> Result<Stmt, LoxError> {
+        let start = self.previous().span;
         self.consume(LEFT_PAREN, String::from("Expect '(' after 'while'."))?;
         let condition = self.expression()?;
         self.consume(RIGHT_PAREN, String::from("Expect ')' after condition."))?;
@@ -219,73 +507,198 @@ impl Parser {
         return Ok(Stmt::While(Rc::new(WhileStmt{
             condition: Rc::new(condition),
             body,
-            is_for_loop: false
+            increment: None,
+            label,
+            span: self.span_from(start)
         })));
 
     }
 
-    fn var_declaration(&mut self) -> Result<Rc<Stmt>, LoxError>{
-        let name = self.consume(IDENTIFIER, String::from("Expect variable name."));
+    fn do_statement(&mut self, label: Option<Token>) -> Result<Stmt, LoxError> {
+        let start = self.previous().span;
+        let body = self.statement()?;
+        self.consume(WHILE, String::from("Expect 'while' after do body."))?;
+        self.consume(LEFT_PAREN, String::from("Expect '(' after 'while'."))?;
+        let condition = self.expression()?;
+        self.consume(RIGHT_PAREN, String::from("Expect ')' after condition."))?;
+        self.consume(SEMICOLON, String::from("Expect ';' after do-while condition."))?;
+
+        Ok(Stmt::DoWhile(Rc::new(DoWhileStmt{
+            body,
+            condition: Rc::new(condition),
+            label,
+            span: self.span_from(start)
+        })))
+    }
+
+    fn loop_statement(&mut self, label: Option<Token>) -> Result<Stmt, LoxError> {
+        let start = self.previous().span;
+        let body = self.statement()?;
+
+        Ok(Stmt::Loop(Rc::new(LoopStmt{
+            body,
+            label,
+            span: self.span_from(start)
+        })))
+    }
+
+    fn var_declaration(&mut self, modifiers: Vec<Token>) -> Result<Rc<VarStmt>, LoxError>{
+        let start = self.start_span(&modifiers);
+        let name = self.consume(IDENTIFIER, String::from("Expect variable name."))?;
         
-        let initializer = if self.is_match(&[EQUAL]) { Some(self.expression()) } else {None};
+        let initializer = if self.is_match(&[EQUAL]) { Some(self.expression()?) } else {None};
 
         self.consume(SEMICOLON, String::from("Expect ';' after variable declaration."))?;
 
-        Ok(Rc::new(Stmt::Var(Rc::new(VarStmt{name: name?, initializer: Some(Rc::new(
+        Ok(Rc::new(VarStmt{initializer: Some(Rc::new(
             match initializer {
                 None => {Expr::Literal(Rc::new(LiteralExpr{
-                    value: Some(Object::Nil)
+                    value: Some(Object::Nil),
+                    span: name.span
                 }))}
-                Some(initializer) => {
-                    initializer?
-                }
+                Some(initializer) => initializer
             }
-        ))}))))
+        )), name, modifiers, span: self.span_from(start)}))
         
     }
 
     fn expression_statement(&mut self) -> Result<Rc<Stmt>, LoxError> {
-        let expr = self.expression();
+        let start = self.peek().span;
+        let expr = self.expression()?;
 
         self.consume(SEMICOLON, String::from("Expect ';' after expression."))?;
         
-        return Ok(Rc::new(Stmt::Expression(Rc::new(ExpressionStmt {expression: Rc::new(expr?)}))));
+        return Ok(Rc::new(Stmt::Expression(Rc::new(ExpressionStmt {expression: Rc::new(expr), span: self.span_from(start)}))));
     }
 
-    fn function(&self, kind: &str) -> Result<Rc<Stmt>, LoxError> {
-        let name = self.consume(IDENTIFIER, String::from(format!("Expect {} name.", kind)));
+    fn function(&mut self, kind: &str, modifiers: Vec<Token>) -> Result<Rc<FunctionStmt>, LoxError> {
+        let start = if modifiers.is_empty() && self.previous().t_type != FUN {
+            self.peek().span
+        } else {
+            self.start_span(&modifiers)
+        };
+        let name = self.consume(IDENTIFIER, String::from(format!("Expect {} name.", kind)))?;
 
-        self.consume(LEFT_PAREN, String::from(format!("Expect '(' after {} name.", kind)));
+        self.consume(LEFT_PAREN, String::from(format!("Expect '(' after {} name.", kind)))?;
+        let parameters = self.parameters()?;
+        self.consume(LEFT_BRACE, String::from(format!("Expect '{{' before {} body.", kind)))?;
+        let body = self.function_body()?;
 
-        let mut parameters: Vec<Token> = Vec::new();
-        loop {
-            if self.is_match(&[COMMA]) {
-                if parameters.len() >= 255 {
-                    let peek_var = self.peek();
-                    return Err(self.error(peek_var, String::from("Can't have more than 255 parameters.")));
-                }
-                parameters.push(self.consume(IDENTIFIER, String::from("Expect parameter name."))?);
-            } else {
+        Ok(Rc::new(FunctionStmt{
+            name,
+            modifiers,
+            params: Rc::new(parameters),
+            body: Rc::new(body),
+            span: self.span_from(start)
+        }))
+    }
+
+    // the parameter list after its '(', up to and including the ')'
+    // a, b = 2, ...rest with an optional trailing comma
+    fn parameters(&mut self) -> Result<Vec<Parameter>, LoxError> {
+        let mut parameters: Vec<Parameter> = Vec::new();
+        while !self.check(RIGHT_PAREN) {
+            let rest = self.is_match(&[DOT_DOT_DOT]);
+            let name = self.consume(IDENTIFIER, String::from("Expect parameter name."))?;
+            if parameters.len() == 255 {
+                let err = self.error(name.clone(), ErrorCode::TooManyArguments, String::from("Can't have more than 255 parameters."));
+                self.errors.push(err);
+            }
+            if parameters.iter().any(|param| param.name.lexeme == name.lexeme) {
+                let err = self.error(name.clone(), ErrorCode::DuplicateParameter, String::from(format!("Duplicate parameter '{}'.", name.lexeme)));
+                self.errors.push(err);
+            }
+            if rest && self.check(EQUAL) {
+                let peek_var = self.peek();
+                return Err(self.error(peek_var, ErrorCode::MisplacedParameter, String::from("A rest parameter can't have a default value.")));
+            }
+            let default = if self.is_match(&[EQUAL]) { Some(Rc::new(self.expression()?)) } else { None };
+
+            if parameters.last().is_some_and(|param| param.rest) {
+                let err = self.error(name.clone(), ErrorCode::MisplacedParameter, String::from("The rest parameter must be the last parameter."));
+                self.errors.push(err);
+            } else if default.is_none() && !rest && parameters.iter().any(|param| param.default.is_some()) {
+                let err = self.error(name.clone(), ErrorCode::MisplacedParameter, String::from("A parameter without a default value can't follow one with a default."));
+                self.errors.push(err);
+            }
+
+            parameters.push(Parameter{name, default, rest});
+            if !self.is_match(&[COMMA]) {
                 break;
             }
         }
 
-        self.consume(RIGHT_PAREN, String::from("Expect ')' after parameters."));
-        self.consume(LEFT_BRACE, String::from(format!("Expect '{{' before {} body.", kind)));
+        self.consume(RIGHT_PAREN, String::from("Expect ')' after parameters."))?;
+        Ok(parameters)
+    }
+
+    // loop labels don't reach into a function body
+    fn function_body(&mut self) -> Result<Vec<Rc<Stmt>>, LoxError> {
+        let enclosing_labels = std::mem::take(&mut self.loop_labels);
         let body = self.block();
+        self.loop_labels = enclosing_labels;
+        body
+    }
 
-        Ok(Rc::new(Stmt::Function(Rc::new(FunctionStmt{
-            name: name?,
+    // fun (a, b) { ... }
+    fn lambda(&mut self) -> Result<Expr, LoxError> {
+        let keyword = self.previous();
+        self.consume(LEFT_PAREN, String::from("Expect '(' after 'fun'."))?;
+        let parameters = self.parameters()?;
+        self.consume(LEFT_BRACE, String::from("Expect '{' before function body."))?;
+        let body = self.function_body()?;
+        Ok(self.lambda_expr(keyword.span, parameters, body))
+    }
+
+    // (a, b) => a + b, or (a, b) => { ... }
+    fn arrow_function(&mut self) -> Result<Expr, LoxError> {
+        let start = self.previous().span;
+        let parameters = self.parameters()?;
+        let arrow = self.consume(EQUAL_GREATER, String::from("Expect '=>' after parameters."))?;
+        let body =
+            if self.is_match(&[LEFT_BRACE]) {
+                self.function_body()?
+            } else {
+                let value = self.expression()?;
+                vec![Rc::new(Stmt::Return(Rc::new(ReturnStmt{span: value.span(), keyword: arrow, value: Some(Rc::new(value))})))]
+            };
+        Ok(self.lambda_expr(start, parameters, body))
+    }
+
+    fn lambda_expr(&self, start: Span, parameters: Vec<Parameter>, body: Vec<Rc<Stmt>>) -> Expr {
+        let span = self.span_from(start);
+        let function = Rc::new(FunctionStmt{
+            name: Token::new(IDENTIFIER, String::from("lambda"), None, start),
+            modifiers: Vec::new(),
             params: Rc::new(parameters),
-            body: Rc::new(body?)
-        }))))
+            body: Rc::new(body),
+            span
+        });
+        Expr::Lambda(Rc::new(LambdaExpr{function, span}))
+    }
+
+    // a '(' starts an arrow function when its matching ')' is followed by '=>'
+    fn is_arrow_function(&self) -> bool {
+        let mut depth = 0;
+        for (i, token) in self.tokens.iter().enumerate().skip(self.current) {
+            match token.t_type {
+                LEFT_PAREN => depth += 1,
+                RIGHT_PAREN if depth == 1 => return self.tokens.get(i + 1).is_some_and(|next| next.t_type == EQUAL_GREATER),
+                RIGHT_PAREN => depth -= 1,
+                EOF => return false,
+                _ => {}
+            }
+        }
+        false
     }
 
     fn block(&mut self) -> Result<Vec<Rc<Stmt>>, LoxError> {
         let mut statements = Vec::new();
 
-        while !self.check(RIGHT_BRACE) && !self.is_at_end() {
-            statements.push(self.declaration()?);
+        while !self.check(RIGHT_BRACE) && !self.is_at_end() && !self.abandoned {
+            if let Some(statement) = self.declaration() {
+                statements.push(statement);
+            }
         }
         self.consume(RIGHT_BRACE, String::from("Expect '}' after block."))?;
         Ok(statements)
@@ -296,13 +709,31 @@ impl Parser {
         
         if self.is_match(&[EQUAL]) {
             let equals = self.previous();
-            let value = self.assignment();
+            let value = self.nested(Self::assignment);
 
             if let Expr::Variable(expr) = expr {
-                return Ok(Expr::Assign(Rc::new(AssignExpr{name: expr.name.clone(),  value: Rc::new(value?)}))) 
+                let value = value?;
+                return Ok(Expr::Assign(Rc::new(AssignExpr{name: expr.name.clone(), span: expr.span.to(value.span()), value: Rc::new(value)}))) 
+            } else if let Expr::Index(index) = expr {
+                let value = value?;
+                return Ok(Expr::IndexSet(Rc::new(IndexSetExpr{
+                    object: Rc::clone(&index.object),
+                    bracket: index.bracket.clone(),
+                    index: Rc::clone(&index.index),
+                    span: index.span.to(value.span()),
+                    value: Rc::new(value)
+                })))
+            } else if let Expr::Get(get) = expr {
+                let value = value?;
+                return Ok(Expr::Set(Rc::new(SetExpr{
+                    object: Rc::clone(&get.object), 
+                    name: get.name.clone(), 
+                    span: get.span.to(value.span()),
+                    value: Rc::new(value)
+                })))
             } else {
                 //TODO: might be wrong
-                return Err(LoxError::error(equals.line, String::from("Invalid assignment target.")))
+                return Err(self.error(equals, ErrorCode::InvalidAssignmentTarget, String::from("Invalid assignment target.")))
             }
         }
         return Ok(expr);
@@ -312,10 +743,12 @@ impl Parser {
         let mut expr = self.and()?;
 
         while self.is_match(&[OR]) {
+            self.deeper()?;
             let operator = self.previous();
             let right = self.and()?;
 
             expr = Expr::Logical(Rc::new(LogicalExpr{
+                span: expr.span().to(right.span()),
                 left: Rc::new(expr),
                 operator,
                 right: Rc::new(right)
@@ -328,9 +761,11 @@ impl Parser {
         let mut expr = self.equality()?;
 
         while self.is_match(&[AND]) {
+            self.deeper()?;
             let operator = self.previous();
             let right = self.equality()?;
             expr = Expr::Logical(Rc::new(LogicalExpr{
+                span: expr.span().to(right.span()),
                 left: Rc::new(expr),
                 operator,
                 right: Rc::new(right)
@@ -343,20 +778,35 @@ impl Parser {
         let mut expr = self.comparison()?;
 
         while self.is_match(&[BANG_EQUAL, EQUAL_EQUAL]) {
+            self.deeper()?;
             let operator = self.previous();
             let right = self.comparison();
-            expr = Expr::Binary(Rc::new(BinaryExpr {left: Rc::new(expr), operator: operator, right: Rc::new(right?)}));
+            let right = right?;
+            expr = Expr::Binary(Rc::new(BinaryExpr {span: expr.span().to(right.span()), left: Rc::new(expr), operator: operator, right: Rc::new(right)}));
         }
         return Ok(expr);
     }
 
     fn comparison(&mut self) -> Result<Expr, LoxError> {
-        let mut expr = self.term()?;
+        let mut expr = self.range()?;
 
         while self.is_match(&[GREATER, GREATER_EQUAL, LESS, LESS_EQUAL]) {
+            self.deeper()?;
             let operator = self.previous();
-            let right = self.term();
-            expr = Expr::Binary(Rc::new(BinaryExpr {left: Rc::new(expr), operator: operator, right: Rc::new(right?)}));
+            let right = self.range();
+            let right = right?;
+            expr = Expr::Binary(Rc::new(BinaryExpr {span: expr.span().to(right.span()), left: Rc::new(expr), operator: operator, right: Rc::new(right)}));
+        }
+        return Ok(expr);
+    }
+
+    fn range(&mut self) -> Result<Expr, LoxError> {
+        let expr = self.term()?;
+
+        if self.is_match(&[DOT_DOT, DOT_DOT_EQUAL]) {
+            let operator = self.previous();
+            let end = self.term()?;
+            return Ok(Expr::Range(Rc::new(RangeExpr {span: expr.span().to(end.span()), start: Rc::new(expr), operator, end: Rc::new(end)})));
         }
         return Ok(expr);
     }
@@ -365,9 +815,11 @@ impl Parser {
         let mut expr = self.factor()?;
 
         while self.is_match(&[MINUS, PLUS]) {
+            self.deeper()?;
             let operator = self.previous();
             let right = self.factor();
-            expr = Expr::Binary(Rc::new(BinaryExpr {left: Rc::new(expr), operator: operator, right: Rc::new(right?)}));
+            let right = right?;
+            expr = Expr::Binary(Rc::new(BinaryExpr {span: expr.span().to(right.span()), left: Rc::new(expr), operator: operator, right: Rc::new(right)}));
         }
 
         return Ok(expr);
@@ -377,9 +829,11 @@ impl Parser {
         let mut expr = self.unary()?;
 
         while self.is_match(&[SLASH, STAR]) {
+            self.deeper()?;
             let operator = self.previous();
             let right = self.unary();
-            expr = Expr::Binary(Rc::new(BinaryExpr {left: Rc::new(expr), operator: operator, right: Rc::new(right?)}));
+            let right = right?;
+            expr = Expr::Binary(Rc::new(BinaryExpr {span: expr.span().to(right.span()), left: Rc::new(expr), operator: operator, right: Rc::new(right)}));
         }
 
         return Ok(expr);
@@ -388,34 +842,46 @@ impl Parser {
     fn unary(&mut self) -> Result<Expr, LoxError> {
         if self.is_match(&[BANG, MINUS]) {
             let operator = self.previous();
-            let right = self.unary();
-            return Ok(Expr::Unary(Rc::new(UnaryExpr {operator: operator, right: Rc::new(right?)})));
+            let right = self.nested(Self::unary)?;
+            return Ok(Expr::Unary(Rc::new(UnaryExpr {span: operator.span.to(right.span()), operator: operator, right: Rc::new(right)})));
         }
             Ok(self.call()?)
     }
 
+    // named arguments come after the positional ones
     fn finish_call(&mut self, callee: Expr) -> Result<Expr, LoxError> {
         let mut arguments = Vec::new();
+        let mut named: Vec<NamedArgument> = Vec::new();
 
-        if !self.check(RIGHT_PAREN) {
-            loop {
-                if arguments.len() >= 255 {
-                    let peek_var = self.peek();
-                    self.error(peek_var, String::from("Can't have more than 255 arguments."));
-                }
+        while !self.check(RIGHT_PAREN) {
+            if arguments.len() + named.len() == 255 {
+                let peek_var = self.peek();
+                let err = self.error(peek_var, ErrorCode::TooManyArguments, String::from("Can't have more than 255 arguments."));
+                self.errors.push(err);
+            }
+            if self.check(IDENTIFIER) && self.check_next(COLON) {
+                let name = self.advance();
+                self.advance();
+                named.push(NamedArgument{name, value: Rc::new(self.expression()?)});
+            } else if !named.is_empty() {
+                let peek_var = self.peek();
+                return Err(self.error(peek_var, ErrorCode::MisplacedParameter, String::from("Positional arguments must come before named arguments.")));
+            } else {
                 arguments.push(Rc::new(self.expression()?));
-                if !self.is_match(&[COMMA]) {
-                    break;
-                }
             }
-        } else {}
+            if !self.is_match(&[COMMA]) {
+                break;
+            }
+        }
 
-        let paren = self.consume(RIGHT_PAREN, String::from("Expect ')' after arguments."));
+        let paren = self.consume(RIGHT_PAREN, String::from("Expect ')' after arguments."))?;
 
         Ok(Expr::Call(Rc::new(CallExpr{
+            span: callee.span().to(paren.span),
             callee: Rc::new(callee),
-            paren: paren?,
-            arguments: arguments
+            paren: paren,
+            arguments: arguments,
+            named
         })))
     }
 
@@ -423,8 +889,20 @@ impl Parser {
         let mut expr = self.primary();
 
         loop {
+            if self.check(LEFT_PAREN) || self.check(DOT) || self.check(LEFT_BRACKET) {
+                self.deeper()?;
+            }
             if self.is_match(&[LEFT_PAREN]) {
                 expr = self.finish_call(expr?);
+            } else if self.is_match(&[DOT]) {
+                let name = self.consume(IDENTIFIER, String::from("Expect property name after '.'."))?;
+                let object = expr?;
+                expr = Ok(Expr::Get(Rc::new(GetExpr{span: object.span().to(name.span), object: Rc::new(object), name})));
+            } else if self.is_match(&[LEFT_BRACKET]) {
+                let index = self.expression()?;
+                let bracket = self.consume(RIGHT_BRACKET, String::from("Expect ']' after index."))?;
+                let object = expr?;
+                expr = Ok(Expr::Index(Rc::new(IndexExpr{span: object.span().to(bracket.span), object: Rc::new(object), bracket, index: Rc::new(index)})));
             } else {
                 break;
             }
@@ -433,37 +911,126 @@ impl Parser {
     }
 
     fn primary(&mut self) -> Result<Expr, LoxError>{
-        if self.is_match(&[FALSE.clone()]) {
-            return Ok(Expr::Literal(Rc::new(LiteralExpr{value: Some(Object::Bool(false))})));
+        if self.is_match(&[FALSE]) {
+            return Ok(Expr::Literal(Rc::new(LiteralExpr{value: Some(Object::Bool(false)), span: self.previous().span})));
         }
         if self.is_match(&[TRUE]) {
-            return Ok(Expr::Literal(Rc::new(LiteralExpr{value: Some(Object::Bool(true))})));
+            return Ok(Expr::Literal(Rc::new(LiteralExpr{value: Some(Object::Bool(true)), span: self.previous().span})));
         }
         if self.is_match(&[NIL]) {
-            return Ok(Expr::Literal(Rc::new(LiteralExpr{value: Some(Object::Nil)})));
+            return Ok(Expr::Literal(Rc::new(LiteralExpr{value: Some(Object::Nil), span: self.previous().span})));
         }
 
         if self.is_match(&[NUMBER, STRING]) {
             //TODO: idk if clone changed anything
-           return Ok(Expr::Literal(Rc::new(LiteralExpr{value: self.previous().literal.clone()})));
+           return Ok(Expr::Literal(Rc::new(LiteralExpr{value: self.previous().literal.clone(), span: self.previous().span})));
+        }
+        if self.is_match(&[INTERPOLATION]) {
+            return self.interpolation();
+        }
+        if self.is_match(&[SUPER]) {
+            let keyword = self.previous();
+            self.consume(DOT, String::from("Expect '.' after 'super'."))?;
+            let method = self.consume(IDENTIFIER, String::from("Expect superclass method name."))?;
+            return Ok(Expr::Super(Rc::new(SuperExpr{span: keyword.span.to(method.span), keyword, method})));
+        }
+        if self.is_match(&[THIS]) {
+            return Ok(Expr::This(Rc::new(ThisExpr{keyword: self.previous(), span: self.previous().span})));
         }
         if self.is_match(&[IDENTIFIER]) {
-           return Ok(Expr::Variable(Rc::new(VariableExpr{name: self.previous()})));
+           return Ok(Expr::Variable(Rc::new(VariableExpr{name: self.previous(), span: self.previous().span})));
+        }
+
+        if self.is_match(&[LEFT_BRACKET]) {
+            return self.array();
+        }
+        if self.is_match(&[HASH_LEFT_BRACE]) {
+            return self.map();
+        }
+        if self.is_match(&[MATCH]) {
+            return self.match_expression();
+        }
+        if self.is_match(&[FUN]) {
+            return self.lambda();
+        }
+        if self.check(LEFT_PAREN) && self.is_arrow_function() {
+            self.advance();
+            return self.arrow_function();
         }
 
         if self.is_match(&[LEFT_PAREN]) {
+            let start = self.previous().span;
             let expr = self.expression();
             self.consume(RIGHT_PAREN, String::from("Expect ')' after expression."))?;
-            return Ok(Expr::Grouping(Rc::new(GroupingExpr {expression: Rc::new(expr?)})));
+            return Ok(Expr::Grouping(Rc::new(GroupingExpr {expression: Rc::new(expr?), span: self.span_from(start)})));
         }
 
         let peek_var = self.peek();
-        Err(LoxError::parse_error(&peek_var, String::from("Expect expression.")))
+        Err(LoxError::parse_error(&peek_var, ErrorCode::ExpectedToken, String::from("Expect expression.")))
+    }
+
+    fn array(&mut self) -> Result<Expr, LoxError> {
+        let bracket = self.previous();
+        let mut elements = Vec::new();
+
+        // a trailing comma before ']' is allowed
+        while !self.check(RIGHT_BRACKET) {
+            elements.push(Rc::new(self.expression()?));
+            if !self.is_match(&[COMMA]) {
+                break;
+            }
+        }
+
+        self.consume(RIGHT_BRACKET, String::from("Expect ']' after array elements."))?;
+        Ok(Expr::Array(Rc::new(ArrayExpr{span: self.span_from(bracket.span), elements})))
+    }
+
+    // the scanner hands over "a ${x} b" as INTERPOLATION("a ") x STRING(" b")
+    fn interpolation(&mut self) -> Result<Expr, LoxError> {
+        let start = self.previous().span;
+        let mut parts = Vec::new();
+
+        loop {
+            parts.push(Rc::new(Expr::Literal(Rc::new(LiteralExpr{value: self.previous().literal.clone(), span: self.previous().span}))));
+            parts.push(Rc::new(self.expression()?));
+            if !self.is_match(&[INTERPOLATION]) {
+                break;
+            }
+        }
+
+        self.consume(STRING, String::from("Expect '}' after interpolated expression."))?;
+        parts.push(Rc::new(Expr::Literal(Rc::new(LiteralExpr{value: self.previous().literal.clone(), span: self.previous().span}))));
+        Ok(Expr::Interpolation(Rc::new(InterpolationExpr{parts, span: self.span_from(start)})))
+    }
+
+    fn map(&mut self) -> Result<Expr, LoxError> {
+        let brace = self.previous();
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+
+        while !self.check(RIGHT_BRACE) {
+            // a bare name before ':' is a string key, anything else is evaluated
+            let key = if self.check(IDENTIFIER) && self.check_next(COLON) {
+                let name = self.advance();
+                Expr::Literal(Rc::new(LiteralExpr{value: Some(Object::String(name.lexeme)), span: name.span}))
+            } else {
+                self.expression()?
+            };
+            self.consume(COLON, String::from("Expect ':' after map key."))?;
+            keys.push(Rc::new(key));
+            values.push(Rc::new(self.expression()?));
+            if !self.is_match(&[COMMA]) {
+                break;
+            }
+        }
+
+        self.consume(RIGHT_BRACE, String::from("Expect '}' after map entries."))?;
+        Ok(Expr::Map(Rc::new(MapExpr{span: self.span_from(brace.span), brace, keys, values})))
     }
 
     fn is_match(&mut self, types: &[TokenType]) -> bool {
         for t_type in types {
-            if self.check(t_type.clone()) {
+            if self.check(*t_type) {
                 self.advance();
                 return true;
             }
@@ -477,7 +1044,7 @@ impl Parser {
             Ok(self.advance())
         } else {
             let peek_var = self.peek();
-            Err(self.error(peek_var, message))
+            Err(self.error(peek_var, ErrorCode::ExpectedToken, message))
         }
     }
 
@@ -488,6 +1055,12 @@ impl Parser {
             return self.peek().t_type == t_type;
         }
     }
+    fn check_next(&self, t_type: TokenType) -> bool {
+        match self.tokens.get(self.current + 1) {
+            Some(token) => token.t_type == t_type,
+            None => false
+        }
+    }
     fn advance(&mut self) -> Token {
         if !self.is_at_end() {
             self.current += 1;
@@ -507,9 +1080,101 @@ impl Parser {
         return self.tokens[self.current-1].clone();
     }
 
-    fn error(&mut self, token: Token, message: String) -> LoxError{
-        self.had_error = true;
-        return LoxError::parse_error(&token, message);
+    // declarations start at their first modifier, or else at the keyword just consumed
+    fn start_span(&self, modifiers: &[Token]) -> Span {
+        match modifiers.first() {
+            Some(modifier) => modifier.span,
+            None => self.previous().span
+        }
+    }
+
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.previous().span)
+    }
+
+    // every rule that can contain itself goes through here, directly or not
+    fn nested<T>(&mut self, rule: impl FnOnce(&mut Self) -> Result<T, LoxError>) -> Result<T, LoxError> {
+        let nesting = self.nesting;
+        self.deeper()?;
+        let result = rule(self);
+        // left-associative operators inside went one level deeper each
+        self.nesting = nesting;
+        result
+    }
+
+    // one level further into the tree, for a nested rule or each operator of a left-associative chain
+    fn deeper(&mut self) -> Result<(), LoxError> {
+        if self.nesting == MAX_NESTING {
+            let peek_var = self.peek();
+            let err = self.error(peek_var, ErrorCode::NestedTooDeeply, format!("Can't nest more than {MAX_NESTING} statements or expressions."));
+            // every enclosing rule would fail on what's left, so none of that is reported
+            self.errors.push(err.clone());
+            self.abandoned = true;
+            self.skip_declaration();
+            return Err(err);
+        }
+        self.nesting += 1;
+        Ok(())
+    }
+
+    // skips to the end of the top-level declaration, where its brackets are all closed again
+    fn skip_declaration(&mut self) {
+        let mut depth = 0usize;
+        for token in &self.tokens[self.declaration_start..self.current] {
+            match token.t_type {
+                LEFT_PAREN | LEFT_BRACE | LEFT_BRACKET | HASH_LEFT_BRACE => depth += 1,
+                RIGHT_PAREN | RIGHT_BRACE | RIGHT_BRACKET => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+
+        while !self.is_at_end() {
+            match self.advance().t_type {
+                LEFT_PAREN | LEFT_BRACE | LEFT_BRACKET | HASH_LEFT_BRACE => depth += 1,
+                RIGHT_PAREN | RIGHT_BRACKET => depth = depth.saturating_sub(1),
+                // a class, function or block ends at its '}', and a lambda's ';' may follow
+                RIGHT_BRACE if depth <= 1 => {
+                    self.is_match(&[SEMICOLON]);
+                    return;
+                }
+                RIGHT_BRACE => depth -= 1,
+                SEMICOLON if depth == 0 => return,
+                _ => {}
+            }
+        }
+    }
+
+    fn error(&mut self, token: Token, code: ErrorCode, message: String) -> LoxError{
+        return LoxError::parse_error(&token, code, message);
+    }
+
+    // skips to the next member of the class body that starts at body_start, or to its closing '}'
+    fn synchronize_member(&mut self, body_start: usize) {
+        let mut depth = 0usize;
+        for token in &self.tokens[body_start..self.current] {
+            match token.t_type {
+                LEFT_PAREN | LEFT_BRACE | LEFT_BRACKET | HASH_LEFT_BRACE => depth += 1,
+                RIGHT_PAREN | RIGHT_BRACE | RIGHT_BRACKET => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+
+        while !self.is_at_end() {
+            if depth == 0 {
+                match self.peek().t_type {
+                    RIGHT_BRACE | VAR | FUN => return,
+                    IDENTIFIER if self.check_next(LEFT_PAREN) => return,
+                    _ if self.check_modifier() => return,
+                    _ => {}
+                }
+            }
+
+            match self.advance().t_type {
+                LEFT_PAREN | LEFT_BRACE | LEFT_BRACKET | HASH_LEFT_BRACE => depth += 1,
+                RIGHT_PAREN | RIGHT_BRACE | RIGHT_BRACKET => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
     }
 
     fn synchronize(&mut self) {
@@ -521,12 +1186,16 @@ impl Parser {
             }
 
             match self.peek().t_type {
+                CLASS => {return;}
                 FUN => {return;}
                 FOR => {return;}
                 VAR => {return;}
                 IF => {return;}
                 WHILE => {return;}
+                DO => {return;}
+                LOOP => {return;}
                 PRINT => {return;}
+                MATCH => {return;}
                 RETURN => {return;}
                 _ => {}
             }
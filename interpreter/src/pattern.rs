@@ -0,0 +1,20 @@
+use crate::object::Object;
+
+// literals match by is_equal, a range matches the numbers from its start up to its end,
+// which is included only for '..='
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    Literal(Object),
+    Range(f64, f64, bool),
+    Wildcard,
+}
+
+// the body is a statement in a match statement and an expression in a match expression
+pub struct MatchArm<T> {
+    pub patterns: Vec<Pattern>,
+    pub body: T,
+}
+
+pub fn is_exhaustive<T>(arms: &[MatchArm<T>]) -> bool {
+    arms.iter().any(|arm| arm.patterns.iter().any(|pattern| matches!(pattern, Pattern::Wildcard)))
+}
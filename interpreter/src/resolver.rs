@@ -0,0 +1,630 @@
+use crate::expr::*;
+use crate::stmt::*;
+use crate::token::Token;
+use crate::tokentype::TokenType;
+use crate::tokentype::TokenType::*;
+use crate::loxclass::has_modifier;
+use crate::error::LoxError;
+use crate::interpreter::Interpreter;
+use crate::diagnostic::*;
+use crate::pattern::*;
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FunctionType {
+    None,
+    Function,
+    Initializer,
+    Method,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+}
+
+// what the resolver knows statically about a class, used to check 'override'
+struct ClassInfo {
+    // None without a superclass, Some(None) when the superclass isn't a class declaration in scope
+    superclass: Option<Option<usize>>,
+    methods: HashSet<String>,
+}
+
+pub struct Resolver<'a> {
+    interpreter: &'a Interpreter,
+    // each scope maps a name to whether its initializer has finished
+    scopes: RefCell<Vec<HashMap<String, bool>>>,
+    current_function: RefCell<FunctionType>,
+    current_class: RefCell<ClassType>,
+    in_static: RefCell<bool>,
+    // keyed by the address of the class declaration
+    classes: RefCell<HashMap<usize, ClassInfo>>,
+    // the class declaration each name in scope stands for, or None if it's something else;
+    // one map per scope, with the globals at the bottom
+    class_bindings: RefCell<Vec<HashMap<String, Option<usize>>>>,
+    loop_depth: RefCell<usize>,
+    loop_labels: RefCell<Vec<String>>,
+    // match statements that an unlabeled break can leave
+    match_depth: RefCell<usize>,
+    errors: RefCell<Vec<LoxError>>,
+    warnings: RefCell<Vec<Diagnostic>>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(interpreter: &'a Interpreter) -> Self {
+        Resolver {
+            interpreter,
+            scopes: RefCell::new(Vec::new()),
+            current_function: RefCell::new(FunctionType::None),
+            current_class: RefCell::new(ClassType::None),
+            in_static: RefCell::new(false),
+            classes: RefCell::new(HashMap::new()),
+            class_bindings: RefCell::new(vec![HashMap::new()]),
+            loop_depth: RefCell::new(0),
+            loop_labels: RefCell::new(Vec::new()),
+            match_depth: RefCell::new(0),
+            errors: RefCell::new(Vec::new()),
+            warnings: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn resolve(&self, statements: &[Rc<Stmt>]) -> Result<(), Vec<LoxError>> {
+        self.resolve_stmts(statements);
+
+        let errors = self.errors.take();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // warnings are reported whether or not resolving succeeded
+    pub fn warnings(&self) -> Vec<Diagnostic> {
+        self.warnings.take()
+    }
+
+    fn resolve_stmts(&self, statements: &[Rc<Stmt>]) {
+        for statement in statements {
+            self.resolve_stmt(statement.clone());
+        }
+    }
+
+    fn resolve_stmt(&self, stmt: Rc<Stmt>) {
+        // errors are recorded in self.errors, so the visit result carries nothing
+        let _ = stmt.accept(self);
+    }
+
+    fn resolve_expr(&self, expr: Rc<Expr>) {
+        let _ = expr.accept(self);
+    }
+
+    fn resolve_function(&self, function: &FunctionStmt, function_type: FunctionType) {
+        let enclosing_function = self.current_function.replace(function_type);
+        let enclosing_loop_depth = self.loop_depth.replace(0);
+        let enclosing_loop_labels = self.loop_labels.replace(Vec::new());
+        let enclosing_match_depth = self.match_depth.replace(0);
+
+        self.begin_scope();
+        for param in function.params.iter() {
+            // a default can use the parameters before it
+            if let Some(default) = param.default.clone() {
+                self.resolve_expr(default);
+            }
+            self.declare(&param.name);
+            self.define(&param.name);
+        }
+        self.resolve_stmts(&function.body);
+        self.end_scope();
+
+        self.loop_depth.replace(enclosing_loop_depth);
+        self.loop_labels.replace(enclosing_loop_labels);
+        self.match_depth.replace(enclosing_match_depth);
+        self.current_function.replace(enclosing_function);
+    }
+
+    fn resolve_loop_body(&self, label: &Option<Token>, body: Rc<Stmt>) {
+        *self.loop_depth.borrow_mut() += 1;
+        if let Some(label) = label {
+            self.loop_labels.borrow_mut().push(label.lexeme.clone());
+        }
+        self.resolve_stmt(body);
+        if label.is_some() {
+            self.loop_labels.borrow_mut().pop();
+        }
+        *self.loop_depth.borrow_mut() -= 1;
+    }
+
+    fn check_exhaustive<T>(&self, keyword: &Token, arms: &[MatchArm<T>]) {
+        if !is_exhaustive(arms) {
+            let message = "Match has no '_' arm, so some values match no pattern.";
+            self.warnings.borrow_mut().push(Diagnostic::new(DiagnosticKind::Lint, ErrorCode::NonExhaustiveMatch, message, Some(Label::new(keyword.span, "add a '_' arm"))));
+        }
+    }
+
+    fn check_label(&self, label: &Option<Token>) {
+        if let Some(label) = label {
+            if !self.loop_labels.borrow().contains(&label.lexeme) {
+                self.error(label, ErrorCode::UndefinedLabel, &format!("No enclosing loop is labeled '{}'.", label.lexeme));
+            }
+        }
+    }
+
+    fn begin_scope(&self) {
+        self.scopes.borrow_mut().push(HashMap::new());
+        self.class_bindings.borrow_mut().push(HashMap::new());
+    }
+
+    fn end_scope(&self) {
+        self.scopes.borrow_mut().pop();
+        self.class_bindings.borrow_mut().pop();
+    }
+
+    fn declare(&self, name: &Token) {
+        if let Some(bindings) = self.class_bindings.borrow_mut().last_mut() {
+            bindings.insert(name.lexeme.clone(), None);
+        }
+        let mut scopes = self.scopes.borrow_mut();
+        if let Some(scope) = scopes.last_mut() {
+            if scope.contains_key(&name.lexeme) {
+                self.error(name, ErrorCode::AlreadyDeclared, "Already a variable with this name in this scope.");
+            }
+            scope.insert(name.lexeme.clone(), false);
+        }
+    }
+
+    fn define(&self, name: &Token) {
+        if let Some(scope) = self.scopes.borrow_mut().last_mut() {
+            scope.insert(name.lexeme.clone(), true);
+        }
+    }
+
+    fn resolve_local(&self, key: usize, name: &Token) {
+        for (depth, scope) in self.scopes.borrow().iter().rev().enumerate() {
+            if scope.contains_key(&name.lexeme) {
+                self.interpreter.resolve(key, depth);
+                return;
+            }
+        }
+        // not found in any scope, so it must be global
+        self.interpreter.resolve_global(key);
+    }
+
+    fn check_class_modifiers(&self, modifiers: &[Token]) {
+        self.check_duplicate_modifiers(modifiers);
+        for modifier in modifiers {
+            if modifier.t_type != DYNAMIC {
+                self.error(modifier, ErrorCode::InvalidModifier, "Only 'dynamic' can modify a class declaration.");
+            }
+        }
+    }
+
+    fn check_member_modifiers(&self, modifiers: &[Token], is_method: bool) {
+        self.check_duplicate_modifiers(modifiers);
+        for modifier in modifiers {
+            match modifier.t_type {
+                DYNAMIC => self.error(modifier, ErrorCode::InvalidModifier, "Only a class can be marked 'dynamic'."),
+                OVERRIDE if !is_method => self.error(modifier, ErrorCode::InvalidModifier, "Only methods can be marked 'override'."),
+                PRIVATE if has_modifier(modifiers, PUBLIC) => self.error(modifier, ErrorCode::InvalidModifier, "A member can't be both 'public' and 'private'."),
+                PRIVATE if has_modifier(modifiers, STATIC) => self.error(modifier, ErrorCode::InvalidModifier, "A member can't be both 'private' and 'static'."),
+                OVERRIDE if has_modifier(modifiers, STATIC) => self.error(modifier, ErrorCode::InvalidModifier, "A static method can't be marked 'override'."),
+                _ => {}
+            }
+        }
+    }
+
+    fn check_duplicate_modifiers(&self, modifiers: &[Token]) {
+        let mut seen: Vec<TokenType> = Vec::new();
+        for modifier in modifiers {
+            if seen.contains(&modifier.t_type) {
+                self.error(modifier, ErrorCode::InvalidModifier, &format!("Duplicate '{}' modifier.", modifier.lexeme));
+            }
+            seen.push(modifier.t_type);
+        }
+    }
+
+    // the class declaration a name refers to here, if it refers to one
+    fn class_named(&self, name: &str) -> Option<usize> {
+        let bindings = self.class_bindings.borrow();
+        bindings.iter().rev().find_map(|scope| scope.get(name)).copied().flatten()
+    }
+
+    // None when some class in the chain isn't known statically; the interpreter checks again
+    fn superclass_has_method(&self, superclass: Option<usize>, method: &str) -> Option<bool> {
+        let classes = self.classes.borrow();
+        let mut current = Some(superclass);
+        while let Some(class) = current {
+            let info = classes.get(&class?)?;
+            if info.methods.contains(method) {
+                return Some(true);
+            }
+            current = info.superclass;
+        }
+        Some(false)
+    }
+
+    fn check_override(&self, stmt: &ClassStmt, method: &FunctionStmt) {
+        let superclass = match stmt.superclass.as_deref() {
+            Some(Expr::Variable(variable)) => self.class_named(&variable.name.lexeme),
+            _ => {
+                self.error(&method.name, ErrorCode::InvalidOverride, &format!("Method '{}' is marked 'override' but class '{}' has no superclass.", method.name.lexeme, stmt.name.lexeme));
+                return;
+            }
+        };
+
+        if self.superclass_has_method(superclass, &method.name.lexeme) == Some(false) {
+            self.error(&method.name, ErrorCode::InvalidOverride, &format!("Method '{}' is marked 'override' but no superclass method has that name.", method.name.lexeme));
+        }
+    }
+
+    fn error(&self, token: &Token, code: ErrorCode, message: &str) {
+        let err = LoxError::parse_error(token, code, String::from(message));
+        self.errors.borrow_mut().push(err);
+    }
+}
+
+impl<'a> StmtVisitor<()> for Resolver<'a> {
+    fn visit_block_stmt(&self, stmt: &BlockStmt) -> Result<(), LoxError> {
+        self.begin_scope();
+        self.resolve_stmts(&stmt.statements);
+        self.end_scope();
+        Ok(())
+    }
+
+    fn visit_break_stmt(&self, stmt: &BreakStmt) -> Result<(), LoxError> {
+        if *self.loop_depth.borrow() == 0 && *self.match_depth.borrow() == 0 {
+            self.error(&stmt.token, ErrorCode::MisplacedJump, "Can't use 'break' outside of a loop or match.");
+        }
+        self.check_label(&stmt.label);
+        Ok(())
+    }
+
+    fn visit_class_stmt(&self, stmt: &ClassStmt) -> Result<(), LoxError> {
+        let enclosing_class = self.current_class.replace(ClassType::Class);
+
+        self.declare(&stmt.name);
+        self.define(&stmt.name);
+        self.check_class_modifiers(&stmt.modifiers);
+
+        if let Some(superclass) = stmt.superclass.clone() {
+            if let Expr::Variable(variable) = superclass.as_ref() {
+                if variable.name.lexeme == stmt.name.lexeme {
+                    self.error(&variable.name, ErrorCode::InheritsFromItself, "A class can't inherit from itself.");
+                }
+            }
+
+            self.current_class.replace(ClassType::Subclass);
+            self.resolve_expr(superclass);
+
+            self.begin_scope();
+            if let Some(scope) = self.scopes.borrow_mut().last_mut() {
+                scope.insert(String::from("super"), true);
+            }
+        }
+
+        // static members live on the class object, outside the scope that binds 'this'
+        let enclosing_static = self.in_static.replace(true);
+        for field in stmt.fields.iter().filter(|field| has_modifier(&field.modifiers, STATIC)) {
+            self.check_member_modifiers(&field.modifiers, false);
+            if let Some(initializer) = field.initializer.clone() {
+                self.resolve_expr(initializer);
+            }
+        }
+        for method in stmt.methods.iter().filter(|method| has_modifier(&method.modifiers, STATIC)) {
+            self.check_member_modifiers(&method.modifiers, true);
+            if method.name.lexeme == "init" {
+                self.error(&method.name, ErrorCode::InvalidModifier, "An initializer can't be 'static'.");
+            }
+            self.resolve_function(method, FunctionType::Method);
+        }
+        self.in_static.replace(false);
+
+        self.begin_scope();
+        if let Some(scope) = self.scopes.borrow_mut().last_mut() {
+            scope.insert(String::from("this"), true);
+        }
+
+        for field in stmt.fields.iter().filter(|field| !has_modifier(&field.modifiers, STATIC)) {
+            self.check_member_modifiers(&field.modifiers, false);
+            if let Some(initializer) = field.initializer.clone() {
+                self.resolve_expr(initializer);
+            }
+        }
+
+        for method in stmt.methods.iter().filter(|method| !has_modifier(&method.modifiers, STATIC)) {
+            self.check_member_modifiers(&method.modifiers, true);
+            if has_modifier(&method.modifiers, OVERRIDE) {
+                self.check_override(stmt, method);
+            }
+
+            let declaration = 
+                if method.name.lexeme == "init" {
+                    FunctionType::Initializer
+                } else {
+                    FunctionType::Method
+                };
+            self.resolve_function(method, declaration);
+        }
+
+        self.end_scope();
+
+        if stmt.superclass.is_some() {
+            self.end_scope();
+        }
+
+        let superclass = match stmt.superclass.as_deref() {
+            Some(Expr::Variable(variable)) => Some(self.class_named(&variable.name.lexeme)),
+            _ => None
+        };
+        let methods = stmt.methods.iter()
+            .filter(|method| !has_modifier(&method.modifiers, STATIC))
+            .map(|method| method.name.lexeme.clone())
+            .collect();
+        let key = Interpreter::expr_key(stmt);
+        self.classes.borrow_mut().insert(key, ClassInfo{superclass, methods});
+        if let Some(bindings) = self.class_bindings.borrow_mut().last_mut() {
+            bindings.insert(stmt.name.lexeme.clone(), Some(key));
+        }
+
+        self.in_static.replace(enclosing_static);
+        self.current_class.replace(enclosing_class);
+        Ok(())
+    }
+
+    fn visit_continue_stmt(&self, stmt: &ContinueStmt) -> Result<(), LoxError> {
+        if *self.loop_depth.borrow() == 0 {
+            self.error(&stmt.token, ErrorCode::MisplacedJump, "Can't use 'continue' outside of a loop.");
+        }
+        self.check_label(&stmt.label);
+        Ok(())
+    }
+
+    fn visit_do_while_stmt(&self, stmt: &DoWhileStmt) -> Result<(), LoxError> {
+        self.resolve_loop_body(&stmt.label, stmt.body.clone());
+        self.resolve_expr(stmt.condition.clone());
+        Ok(())
+    }
+
+    fn visit_expression_stmt(&self, stmt: &ExpressionStmt) -> Result<(), LoxError> {
+        self.resolve_expr(stmt.expression.clone());
+        Ok(())
+    }
+
+    fn visit_for_in_stmt(&self, stmt: &ForInStmt) -> Result<(), LoxError> {
+        self.resolve_expr(stmt.iterable.clone());
+
+        self.begin_scope();
+        self.declare(&stmt.name);
+        self.define(&stmt.name);
+        self.resolve_loop_body(&stmt.label, stmt.body.clone());
+        self.end_scope();
+        Ok(())
+    }
+
+    fn visit_function_stmt(&self, stmt: &FunctionStmt) -> Result<(), LoxError> {
+        self.declare(&stmt.name);
+        self.define(&stmt.name);
+
+        self.resolve_function(stmt, FunctionType::Function);
+        Ok(())
+    }
+
+    fn visit_if_stmt(&self, stmt: &IfStmt) -> Result<(), LoxError> {
+        self.resolve_expr(stmt.condition.clone());
+        self.resolve_stmt(stmt.then_branch.clone());
+        if let Some(else_branch) = stmt.else_branch.clone() {
+            self.resolve_stmt(else_branch);
+        }
+        Ok(())
+    }
+
+    fn visit_loop_stmt(&self, stmt: &LoopStmt) -> Result<(), LoxError> {
+        self.resolve_loop_body(&stmt.label, stmt.body.clone());
+        Ok(())
+    }
+
+    fn visit_match_stmt(&self, stmt: &MatchStmt) -> Result<(), LoxError> {
+        self.resolve_expr(stmt.subject.clone());
+        self.check_exhaustive(&stmt.keyword, &stmt.arms);
+
+        *self.match_depth.borrow_mut() += 1;
+        for arm in stmt.arms.iter() {
+            self.resolve_stmt(arm.body.clone());
+        }
+        *self.match_depth.borrow_mut() -= 1;
+        Ok(())
+    }
+
+    fn visit_print_stmt(&self, stmt: &PrintStmt) -> Result<(), LoxError> {
+        self.resolve_expr(stmt.expression.clone());
+        Ok(())
+    }
+
+    fn visit_return_stmt(&self, stmt: &ReturnStmt) -> Result<(), LoxError> {
+        if *self.current_function.borrow() == FunctionType::None {
+            self.error(&stmt.keyword, ErrorCode::MisplacedReturn, "Can't return from top-level code.");
+        }
+
+        if let Some(value) = stmt.value.clone() {
+            if *self.current_function.borrow() == FunctionType::Initializer {
+                self.error(&stmt.keyword, ErrorCode::MisplacedReturn, "Can't return a value from an initializer.");
+            }
+            self.resolve_expr(value);
+        }
+        Ok(())
+    }
+
+    fn visit_var_stmt(&self, stmt: &VarStmt) -> Result<(), LoxError> {
+        self.declare(&stmt.name);
+        if let Some(initializer) = stmt.initializer.clone() {
+            self.resolve_expr(initializer);
+        }
+        self.define(&stmt.name);
+        Ok(())
+    }
+
+    fn visit_while_stmt(&self, stmt: &WhileStmt) -> Result<(), LoxError> {
+        self.resolve_expr(stmt.condition.clone());
+        self.resolve_loop_body(&stmt.label, stmt.body.clone());
+        if let Some(increment) = stmt.increment.clone() {
+            self.resolve_expr(increment);
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ExprVisitor<()> for Resolver<'a> {
+    fn visit_array_expr(&self, expr: &ArrayExpr) -> Result<(), LoxError> {
+        for element in expr.elements.iter() {
+            self.resolve_expr(element.clone());
+        }
+        Ok(())
+    }
+
+    fn visit_interpolation_expr(&self, expr: &InterpolationExpr) -> Result<(), LoxError> {
+        for part in expr.parts.iter() {
+            self.resolve_expr(part.clone());
+        }
+        Ok(())
+    }
+
+    fn visit_map_expr(&self, expr: &MapExpr) -> Result<(), LoxError> {
+        for (key, value) in expr.keys.iter().zip(expr.values.iter()) {
+            self.resolve_expr(key.clone());
+            self.resolve_expr(value.clone());
+        }
+        Ok(())
+    }
+
+    fn visit_assign_expr(&self, expr: &AssignExpr) -> Result<(), LoxError> {
+        self.resolve_expr(expr.value.clone());
+        self.resolve_local(Interpreter::expr_key(expr), &expr.name);
+        Ok(())
+    }
+
+    fn visit_binary_expr(&self, expr: &BinaryExpr) -> Result<(), LoxError> {
+        self.resolve_expr(expr.left.clone());
+        self.resolve_expr(expr.right.clone());
+        Ok(())
+    }
+
+    fn visit_call_expr(&self, expr: &CallExpr) -> Result<(), LoxError> {
+        self.resolve_expr(expr.callee.clone());
+
+        for argument in expr.arguments.iter() {
+            self.resolve_expr(argument.clone());
+        }
+        for argument in expr.named.iter() {
+            self.resolve_expr(argument.value.clone());
+        }
+        Ok(())
+    }
+
+    fn visit_get_expr(&self, expr: &GetExpr) -> Result<(), LoxError> {
+        self.resolve_expr(expr.object.clone());
+        Ok(())
+    }
+
+    fn visit_grouping_expr(&self, expr: &GroupingExpr) -> Result<(), LoxError> {
+        self.resolve_expr(expr.expression.clone());
+        Ok(())
+    }
+
+    fn visit_index_expr(&self, expr: &IndexExpr) -> Result<(), LoxError> {
+        self.resolve_expr(expr.object.clone());
+        self.resolve_expr(expr.index.clone());
+        Ok(())
+    }
+
+    fn visit_index_set_expr(&self, expr: &IndexSetExpr) -> Result<(), LoxError> {
+        self.resolve_expr(expr.value.clone());
+        self.resolve_expr(expr.object.clone());
+        self.resolve_expr(expr.index.clone());
+        Ok(())
+    }
+
+    fn visit_literal_expr(&self, _expr: &LiteralExpr) -> Result<(), LoxError> {
+        Ok(())
+    }
+
+    fn visit_logical_expr(&self, expr: &LogicalExpr) -> Result<(), LoxError> {
+        self.resolve_expr(expr.left.clone());
+        self.resolve_expr(expr.right.clone());
+        Ok(())
+    }
+
+    fn visit_match_expr(&self, expr: &MatchExpr) -> Result<(), LoxError> {
+        self.resolve_expr(expr.subject.clone());
+        self.check_exhaustive(&expr.keyword, &expr.arms);
+        for arm in expr.arms.iter() {
+            self.resolve_expr(arm.body.clone());
+        }
+        Ok(())
+    }
+
+    fn visit_lambda_expr(&self, expr: &LambdaExpr) -> Result<(), LoxError> {
+        self.resolve_function(&expr.function, FunctionType::Function);
+        Ok(())
+    }
+
+    fn visit_range_expr(&self, expr: &RangeExpr) -> Result<(), LoxError> {
+        self.resolve_expr(expr.start.clone());
+        self.resolve_expr(expr.end.clone());
+        Ok(())
+    }
+
+    fn visit_set_expr(&self, expr: &SetExpr) -> Result<(), LoxError> {
+        self.resolve_expr(expr.value.clone());
+        self.resolve_expr(expr.object.clone());
+        Ok(())
+    }
+
+    fn visit_super_expr(&self, expr: &SuperExpr) -> Result<(), LoxError> {
+        match *self.current_class.borrow() {
+            ClassType::None => self.error(&expr.keyword, ErrorCode::MisplacedThisOrSuper, "Can't use 'super' outside of a class."),
+            ClassType::Class => self.error(&expr.keyword, ErrorCode::MisplacedThisOrSuper, "Can't use 'super' in a class with no superclass."),
+            ClassType::Subclass if *self.in_static.borrow() => self.error(&expr.keyword, ErrorCode::MisplacedThisOrSuper, "Can't use 'super' in a static member."),
+            ClassType::Subclass => {}
+        }
+
+        self.resolve_local(Interpreter::expr_key(expr), &expr.keyword);
+        Ok(())
+    }
+
+    fn visit_this_expr(&self, expr: &ThisExpr) -> Result<(), LoxError> {
+        if *self.current_class.borrow() == ClassType::None {
+            self.error(&expr.keyword, ErrorCode::MisplacedThisOrSuper, "Can't use 'this' outside of a class.");
+            return Ok(());
+        }
+        if *self.in_static.borrow() {
+            self.error(&expr.keyword, ErrorCode::MisplacedThisOrSuper, "Can't use 'this' in a static member.");
+            return Ok(());
+        }
+
+        self.resolve_local(Interpreter::expr_key(expr), &expr.keyword);
+        Ok(())
+    }
+
+    fn visit_unary_expr(&self, expr: &UnaryExpr) -> Result<(), LoxError> {
+        self.resolve_expr(expr.right.clone());
+        Ok(())
+    }
+
+    fn visit_variable_expr(&self, expr: &VariableExpr) -> Result<(), LoxError> {
+        let uninitialized = match self.scopes.borrow().last() {
+            Some(scope) => scope.get(&expr.name.lexeme) == Some(&false),
+            None => false,
+        };
+        if uninitialized {
+            self.error(&expr.name, ErrorCode::ReadInOwnInitializer, "Can't read local variable in its own initializer.");
+        }
+
+        self.resolve_local(Interpreter::expr_key(expr), &expr.name);
+        Ok(())
+    }
+}
@@ -2,24 +2,46 @@ use crate::token::*;
 use crate::tokentype::TokenType;
 use crate::object::*;
 use crate::error::*;
+use crate::diagnostic::ErrorCode;
+use crate::span::Span;
+
+use std::cell::Cell;
 
 #[derive(Clone)]
 pub struct Scanner {
     source: String,
+    file: usize,
     tokens: Vec<Token>,
     start: usize,
     current: usize,
     line: usize,
+    // where the token being scanned begins
+    start_line: usize,
+    start_column: usize,
+    // byte offset where the current line begins, for error columns
+    line_start: usize,
+    // the last offset a column was counted for, and its column, so a long line isn't recounted for every token
+    last_column: Cell<(usize, usize)>,
+    // the '${' and open '{' count of each enclosing ${...}, innermost last
+    interpolations: Vec<(Span, usize)>,
+    errors: Vec<LoxError>,
 }
 
 impl Scanner {
-    pub fn new(source: String) -> Scanner {
+    pub fn new(source: String, file: usize) -> Scanner {
         Scanner {
             source: source.to_owned(),
+            file,
             tokens: Vec::new(),
             start:0,
             current:0,
             line:1,
+            start_line:1,
+            start_column:1,
+            line_start:0,
+            last_column: Cell::new((0, 1)),
+            interpolations: Vec::new(),
+            errors: Vec::new(),
 
         }
     }
@@ -27,6 +49,7 @@ impl Scanner {
     fn keyword(&self, check: &str) -> Option<TokenType> {
         match check {
             "and" => Some(TokenType::AND),
+            "class" => Some(TokenType::CLASS),
             "else" => Some(TokenType::ELSE),
             "false" => Some(TokenType::FALSE),
             "for" => Some(TokenType::FOR),
@@ -36,43 +59,48 @@ impl Scanner {
             "or" => Some(TokenType::OR),
             "print" => Some(TokenType::PRINT),
             "return" => Some(TokenType::RETURN),
-            //no in heriitance or classes
-            // "super" => Some(TokenType::SUPER),
-            // "this" => Some(TokenType::THIS),
+            "super" => Some(TokenType::SUPER),
+            "this" => Some(TokenType::THIS),
             "true" => Some(TokenType::TRUE),
             "var" => Some(TokenType::VAR),
             "while" => Some(TokenType::WHILE),
             "break" => Some(TokenType::BREAK),
             "continue" => Some(TokenType::CONTINUE),
+            "match" => Some(TokenType::MATCH),
+            "do" => Some(TokenType::DO),
+            "loop" => Some(TokenType::LOOP),
+            "in" => Some(TokenType::IN),
+            "private" => Some(TokenType::PRIVATE),
+            "public" => Some(TokenType::PUBLIC),
+            "dynamic" => Some(TokenType::DYNAMIC),
+            "static" => Some(TokenType::STATIC),
+            "override" => Some(TokenType::OVERRIDE),
             _ => None,
         }
     }
 
-    pub fn scan_tokens(&mut self) -> Result<Vec<Token>, LoxError> {
-
-        let mut had_error: Option<LoxError> = None;
-        
+    // scanning carries on past an error so that every bad token is reported;
+    // the tokens come back either way so the parser can report its errors too
+    pub fn scan_tokens(&mut self) -> (Vec<Token>, Vec<LoxError>) {
         while !self.is_at_end() {
             self.start = self.current;
-            match self.scan_token() {
-                Ok(_) => {},
-                Err(e) =>
-                {had_error = Some(e);}
+            self.start_line = self.line;
+            self.start_column = self.column(self.start);
+            if let Err(e) = self.scan_token() {
+                self.errors.push(e);
+                self.add_token(TokenType::ERROR);
             }
         }
 
-        self.tokens.push(Token{
-            t_type: TokenType::EOF,
-            lexeme: String::from(""),
-            literal: None,
-            line: self.line,
-
-        });
-        if let Some(e) = had_error {
-            Err(e)
-        } else {
-            Ok(self.tokens.clone())
+        if let Some(&(opener, _)) = self.interpolations.last() {
+            self.errors.push(LoxError::scan_error(opener, ErrorCode::UnterminatedInterpolation, String::from("Unterminated string interpolation.")));
         }
+
+        self.start = self.current;
+        self.start_line = self.line;
+        self.start_column = self.column(self.start);
+        self.add_token(TokenType::EOF);
+        (self.tokens.clone(), std::mem::take(&mut self.errors))
     }
 
     fn is_at_end(&mut self) -> bool {
@@ -87,13 +115,47 @@ impl Scanner {
         match c {
             '(' => self.add_token(TokenType::LEFT_PAREN),
             ')' => self.add_token(TokenType::RIGHT_PAREN),
-            '{' => self.add_token(TokenType::LEFT_BRACE),
-            '}' => self.add_token(TokenType::RIGHT_BRACE),
+            '{' => {
+                if let Some((_, depth)) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(TokenType::LEFT_BRACE)
+            }
+            '}' if matches!(self.interpolations.last(), Some((_, 0))) => {
+                self.interpolations.pop();
+                self.string(false)?
+            }
+            '}' => {
+                if let Some((_, depth)) = self.interpolations.last_mut() {
+                    *depth -= 1;
+                }
+                self.add_token(TokenType::RIGHT_BRACE)
+            }
+            '[' => self.add_token(TokenType::LEFT_BRACKET),
+            ']' => self.add_token(TokenType::RIGHT_BRACKET),
             ',' => self.add_token(TokenType::COMMA),
-            '.' => self.add_token(TokenType::DOT),
+            '.' => {
+                let token = if !self.is_match('.') {
+                    TokenType::DOT
+                } else if self.is_match('.') {
+                    TokenType::DOT_DOT_DOT
+                } else if self.is_match('=') {
+                    TokenType::DOT_DOT_EQUAL
+                } else {
+                    TokenType::DOT_DOT
+                };
+                self.add_token(token)
+            }
             '-' => self.add_token(TokenType::MINUS),
             '+' => self.add_token(TokenType::PLUS),
             ';' => self.add_token(TokenType::SEMICOLON),
+            ':' => self.add_token(TokenType::COLON),
+            '#' if self.is_match('{') => {
+                if let Some((_, depth)) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(TokenType::HASH_LEFT_BRACE)
+            }
             '*' => self.add_token(TokenType::STAR),
             '!' => {
                 let sec_char = self.is_match('=');
@@ -104,12 +166,14 @@ impl Scanner {
                 })
             }
             '=' => {
-                let sec_char = self.is_match('=');
-                self.add_token(if sec_char {
+                let token = if self.is_match('=') {
                     TokenType::EQUAL_EQUAL
+                } else if self.is_match('>') {
+                    TokenType::EQUAL_GREATER
                 } else {
                     TokenType::EQUAL
-                })
+                };
+                self.add_token(token)
             }
             '<' => {
                 let sec_char = self.is_match('=');
@@ -140,19 +204,18 @@ impl Scanner {
                 }
             }
             ' ' | '\r' | '\t' => {}
-            '\n' => {
-                self.line = self.line + 1;
-            }
-            '"' => self.string(),
+            '\n' => self.newline(),
+            '"' => self.string(false)?,
+            '@' if self.is_match('"') => self.string(true)?,
             c => {
                 if self.is_digit(c) {
-                    self.number();
+                    self.number()?;
                 } 
                 else if self.is_alpha(c) {
                     self.identifier();
                 } 
                 else {
-                    LoxError::error(self.line, String::from("Unexpected character."));
+                    return Err(self.error_at(self.start, ErrorCode::UnexpectedCharacter, String::from("Unexpected character.")));
                 }
             }
             
@@ -177,17 +240,15 @@ impl Scanner {
         }
     }
 
-    pub fn number(&mut self) {
+    pub fn number(&mut self) -> Result<(), LoxError> {
         while self.peek().is_ascii_digit() {
             if !self.is_at_end() {
                 self.advance();
             }
         } 
 
-        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
-            if !self.is_at_end() {
-                self.advance();
-            }
+        if self.peek() == '.' && self.peek_next().is_ascii_digit() && !self.is_at_end() {
+            self.advance();
         }
 
         while self.peek().is_ascii_digit() {
@@ -197,75 +258,194 @@ impl Scanner {
         } 
 
         let s = &self.source[self.start..self.current];
-        let num = s.parse::<f64>().unwrap();
+        let num = match s.parse::<f64>() {
+            Ok(num) => num,
+            Err(_) => return Err(self.error_at(self.start, ErrorCode::InvalidNumber, String::from("Invalid number."))),
+        };
 
         self.add_token_fr(TokenType::NUMBER, Some(Object::Number(num)));
+        Ok(())
     }
 
-    pub fn string(&mut self) {
-        let mut peek_var = self.peek();
-        while peek_var != '"' && !self.is_at_end() {
-            if peek_var == '\n' {
-                self.line = self.line + 1;
+    // raw strings keep backslashes as written and spell a quote as ""
+    // a "${" ends the token as an INTERPOLATION part; the matching '}' resumes the string
+    pub fn string(&mut self, raw: bool) -> Result<(), LoxError> {
+        let mut value = String::new();
+        let mut segment = self.current;
+
+        loop {
+            if self.is_at_end() {
+                let span = Span {
+                    file: self.file,
+                    start: self.start,
+                    end: self.current,
+                    line: self.start_line,
+                    column: self.start_column,
+                };
+                return Err(LoxError::scan_error(span, ErrorCode::UnterminatedString, String::from("Unterminated string.")));
+            }
+            match self.peek() {
+                '"' if raw && self.peek_next() == '"' => {
+                    value.push_str(&self.source[segment..self.current + 1]);
+                    self.current += 2;
+                    segment = self.current;
+                }
+                '"' => break,
+                '$' if !raw && self.peek_next() == '{' => {
+                    value.push_str(&self.source[segment..self.current]);
+                    let opener = Span {
+                        file: self.file,
+                        start: self.current,
+                        end: self.current + 2,
+                        line: self.line,
+                        column: self.column(self.current),
+                    };
+                    self.current += 2;
+                    self.interpolations.push((opener, 0));
+                    self.add_token_fr(TokenType::INTERPOLATION, Some(Object::String(value)));
+                    return Ok(());
+                }
+                '\\' if !raw => {
+                    value.push_str(&self.source[segment..self.current]);
+                    match self.escape() {
+                        Ok(c) => value.push(c),
+                        Err(e) => self.errors.push(e),
+                    }
+                    segment = self.current;
+                }
+                '\n' => {
+                    self.advance();
+                    self.newline();
+                }
+                _ => {
+                    self.advance();
+                }
             }
-                self.advance();
-            peek_var = self.peek();
         }
 
+        value.push_str(&self.source[segment..self.current]);
+        self.advance();
+
+        self.add_token_fr(TokenType::STRING, Some(Object::String(value)));
+        Ok(())
+    }
+
+    fn escape(&mut self) -> Result<char, LoxError> {
+        let backslash = self.current;
+        self.advance();
         if self.is_at_end() {
-            LoxError::error(self.line, String::from("Unterminated string."));
-        } else { 
+            return Err(self.error_at(backslash, ErrorCode::InvalidEscape, String::from("Unterminated escape sequence.")));
+        }
+
+        match self.advance() {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            '$' => Ok('$'),
+            'u' => self.unicode_escape(backslash),
+            '\n' => {
+                let err = self.error_at(backslash, ErrorCode::InvalidEscape, String::from("Invalid escape sequence '\\' at end of line."));
+                self.newline();
+                Err(err)
+            }
+            _ => {
+                let text = self.source[backslash..].chars().take(2).collect::<String>();
+                Err(self.error_at(backslash, ErrorCode::InvalidEscape, format!("Invalid escape sequence '{text}'.")))
+            }
+        }
+    }
+
+    fn unicode_escape(&mut self, backslash: usize) -> Result<char, LoxError> {
+        if !self.is_match('{') {
+            return Err(self.error_at(backslash, ErrorCode::InvalidUnicodeEscape, String::from("Expect '{' after '\\u'.")));
+        }
+
+        let digits_start = self.current;
+        while self.peek().is_ascii_hexdigit() {
             self.advance();
         }
+        let digits = self.source[digits_start..self.current].to_string();
 
+        if !self.is_match('}') {
+            return Err(self.error_at(backslash, ErrorCode::InvalidUnicodeEscape, String::from("Expect '}' after unicode escape digits.")));
+        }
+        if digits.is_empty() || digits.len() > 6 {
+            return Err(self.error_at(backslash, ErrorCode::InvalidUnicodeEscape, String::from("Unicode escape must have 1 to 6 hex digits.")));
+        }
 
-        let value = String::from(&self.source[self.start+1..self.current-1]);
-        self.add_token_fr(TokenType::STRING, Some(Object::String(value)));
+        match u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32) {
+            Some(c) => Ok(c),
+            None => Err(self.error_at(backslash, ErrorCode::InvalidUnicodeEscape, format!("Invalid unicode scalar value '\\u{{{digits}}}'.")))
+        }
+    }
+
+    fn newline(&mut self) {
+        self.line += 1;
+        self.line_start = self.current;
+    }
 
+    // columns count chars, not bytes
+    fn column(&self, offset: usize) -> usize {
+        let (from, column) = match self.last_column.get() {
+            (last, column) if last >= self.line_start && last <= offset => (last, column),
+            _ => (self.line_start, 1),
+        };
+        let column = column + self.source[from..offset].chars().count();
+        self.last_column.set((offset, column));
+        column
+    }
+
+    // the error covers the source from offset up to what has been scanned so far
+    fn error_at(&self, offset: usize, code: ErrorCode, message: String) -> LoxError {
+        let span = Span {
+            file: self.file,
+            start: offset,
+            end: self.current,
+            line: self.line,
+            column: self.column(offset),
+        };
+        LoxError::scan_error(span, code, message)
     }
 
     pub fn is_match(&mut self, expected: char) -> bool{
         if self.is_at_end() {
             return false;
         }
-        if self.source.as_bytes()[self.current] as char != expected {
+        if self.peek() != expected {
             return false;
         }
-        self.current = self.current + 1;
+        self.current += expected.len_utf8();
         return true;
     }
 
+    // current and start are byte offsets, always kept on char boundaries
     pub fn peek(&mut self ) -> char {
-        if self.is_at_end() {
-            return '\0';
-        }
-        return self.source.as_bytes()[self.current] as char;
+        return self.source[self.current..].chars().next().unwrap_or('\0');
     }
 
     pub fn peek_next(&mut self) -> char{
-        if self.current + 1>= self.source.len() {
-            return '\0';
-        }
-        return self.source.as_bytes()[self.current + 1] as char;
+        return self.source[self.current..].chars().nth(1).unwrap_or('\0');
     }
 
     pub fn is_digit(&mut self, c: char) -> bool {
         return c.is_ascii_digit();
     }
     
+    // identifiers follow UAX #31, plus '_' as a start character
     pub fn is_alpha(&mut self, c: char) -> bool {
-        return c.is_alphabetic() || c == '_';
+        return unicode_ident::is_xid_start(c) || c == '_';
     }
 
     pub fn is_alpha_numeric(&mut self, c: char) -> bool {
-        return self.is_alpha(c) || self.is_digit(c);
+        return unicode_ident::is_xid_continue(c);
     }
 
     pub fn advance(&mut self) -> char {
-
-        let result = self.source.as_bytes()[self.current];
-        self.current += 1;
-        result as char
+        let result = self.peek();
+        self.current += result.len_utf8();
+        result
     }
 
     pub fn add_token(&mut self, t_type: TokenType) {
@@ -278,7 +458,13 @@ impl Scanner {
             t_type,
             lexeme: String::from(text),
             literal,
-            line: self.line
+            span: Span {
+                file: self.file,
+                start: self.start,
+                end: self.current,
+                line: self.start_line,
+                column: self.start_column,
+            }
         })
     }
 }
\ No newline at end of file
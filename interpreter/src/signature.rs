@@ -0,0 +1,108 @@
+use crate::expr::Expr;
+use crate::object::Object;
+use crate::loxarray::LoxArray;
+use crate::token::Token;
+use crate::error::LoxError;
+use crate::diagnostic::ErrorCode;
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+// a parameter as declared; defaults are evaluated at call time in the function's scope
+pub struct Parameter {
+    pub name: Token,
+    pub default: Option<Rc<Expr>>,
+    pub rest: bool,
+}
+
+// f(b: 3) passes 3 to the parameter named b
+pub struct NamedArgument {
+    pub name: Token,
+    pub value: Rc<Expr>,
+}
+
+// what a callable accepts, for functions and natives alike
+#[derive(Clone, Debug, Default)]
+pub struct Signature {
+    // every parameter name in order, the rest parameter last
+    pub params: Vec<String>,
+    // how many leading parameters have no default
+    pub required: usize,
+    pub rest: bool,
+}
+
+impl Signature {
+    // natives take a fixed list of parameters, which can still be passed by name
+    pub fn new(params: &[&str]) -> Self {
+        Signature {
+            params: params.iter().map(|param| param.to_string()).collect(),
+            required: params.len(),
+            rest: false,
+        }
+    }
+
+    pub fn from_parameters(params: &[Parameter]) -> Self {
+        Signature {
+            params: params.iter().map(|param| param.name.lexeme.clone()).collect(),
+            required: params.iter().take_while(|param| param.default.is_none() && !param.rest).count(),
+            rest: params.last().is_some_and(|param| param.rest),
+        }
+    }
+
+    // the parameters that take a single argument each
+    fn fixed(&self) -> usize {
+        self.params.len() - self.rest as usize
+    }
+
+    pub fn accepts(&self, count: usize) -> bool {
+        count >= self.required && (self.rest || count <= self.fixed())
+    }
+
+    // "2 arguments", "1 to 3 arguments", "at least 1 argument"
+    pub fn describe(&self) -> String {
+        let count = if self.rest {
+            format!("at least {}", self.required)
+        } else if self.required == self.fixed() {
+            self.required.to_string()
+        } else {
+            format!("{} to {}", self.required, self.fixed())
+        };
+        let noun = if count == "1" || count == "at least 1" { "argument" } else { "arguments" };
+        format!("{count} {noun}")
+    }
+
+    // one slot per parameter: None where the default applies, and the rest parameter as an array
+    pub fn bind(&self, positional: Vec<Object>, named: Vec<(Token, Object)>, paren: &Token) -> Result<Vec<Option<Object>>, LoxError> {
+        let count = positional.len() + named.len();
+        if !self.accepts(count) {
+            return Err(LoxError::runtime_error(paren, ErrorCode::ArityMismatch, String::from(format!("Expected {} but got {}.", self.describe(), count))));
+        }
+
+        let fixed = self.fixed();
+        let mut positional = positional.into_iter();
+        let mut slots: Vec<Option<Object>> = positional.by_ref().take(fixed).map(Some).collect();
+        slots.resize(fixed, None);
+        let rest: Vec<Object> = positional.collect();
+
+        for (name, value) in named {
+            let index = match self.params.iter().position(|param| *param == name.lexeme) {
+                Some(index) if index < fixed => index,
+                Some(_) => return Err(LoxError::runtime_error(&name, ErrorCode::InvalidNamedArgument, String::from(format!("Rest parameter '{}' can't be passed by name.", name.lexeme)))),
+                None => return Err(LoxError::runtime_error(&name, ErrorCode::InvalidNamedArgument, String::from(format!("No parameter named '{}'.", name.lexeme)))),
+            };
+            if slots[index].is_some() {
+                return Err(LoxError::runtime_error(&name, ErrorCode::InvalidNamedArgument, String::from(format!("Parameter '{}' was already given an argument.", name.lexeme))));
+            }
+            slots[index] = Some(value);
+        }
+
+        if let Some(missing) = slots.iter().take(self.required).position(|slot| slot.is_none()) {
+            return Err(LoxError::runtime_error(paren, ErrorCode::ArityMismatch, String::from(format!("Missing argument for parameter '{}'.", self.params[missing]))));
+        }
+
+        if self.rest {
+            slots.push(Some(Object::Array(Rc::new(RefCell::new(LoxArray::from(rest))))));
+        }
+        Ok(slots)
+    }
+}
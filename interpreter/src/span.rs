@@ -0,0 +1,19 @@
+// a range of source text; start and end are byte offsets, line and column are 1-based
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Span {
+    pub file: usize,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    // from the start of this span through the end of the other
+    pub fn to(&self, other: Span) -> Span {
+        Span {
+            end: self.end.max(other.end),
+            ..*self
+        }
+    }
+}
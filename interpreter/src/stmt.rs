@@ -1,16 +1,25 @@
 use crate::error::*;
 use crate::expr::*;
 use crate::token::*;
+use crate::span::*;
+use crate::pattern::*;
+use crate::signature::*;
 use std::rc::Rc;
 
 pub enum Stmt {
     Block(Rc<BlockStmt>),
     Break(Rc<BreakStmt>),
+    Class(Rc<ClassStmt>),
     Continue(Rc<ContinueStmt>),
+    DoWhile(Rc<DoWhileStmt>),
     Expression(Rc<ExpressionStmt>),
+    ForIn(Rc<ForInStmt>),
     Function(Rc<FunctionStmt>),
     If(Rc<IfStmt>),
+    Loop(Rc<LoopStmt>),
+    Match(Rc<MatchStmt>),
     Print(Rc<PrintStmt>),
+    Return(Rc<ReturnStmt>),
     Var(Rc<VarStmt>),
     While(Rc<WhileStmt>),
 }
@@ -20,70 +29,160 @@ impl Stmt {
         match self {
             Stmt::Block(v) => v.accept(stmt_visitor),
             Stmt::Break(v) => v.accept(stmt_visitor),
+            Stmt::Class(v) => v.accept(stmt_visitor),
             Stmt::Continue(v) => v.accept(stmt_visitor),
+            Stmt::DoWhile(v) => v.accept(stmt_visitor),
             Stmt::Expression(v) => v.accept(stmt_visitor),
+            Stmt::ForIn(v) => v.accept(stmt_visitor),
             Stmt::Function(v) => v.accept(stmt_visitor),
             Stmt::If(v) => v.accept(stmt_visitor),
+            Stmt::Loop(v) => v.accept(stmt_visitor),
+            Stmt::Match(v) => v.accept(stmt_visitor),
             Stmt::Print(v) => v.accept(stmt_visitor),
+            Stmt::Return(v) => v.accept(stmt_visitor),
             Stmt::Var(v) => v.accept(stmt_visitor),
             Stmt::While(v) => v.accept(stmt_visitor),
         }
     }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Block(v) => v.span,
+            Stmt::Break(v) => v.span,
+            Stmt::Class(v) => v.span,
+            Stmt::Continue(v) => v.span,
+            Stmt::DoWhile(v) => v.span,
+            Stmt::Expression(v) => v.span,
+            Stmt::ForIn(v) => v.span,
+            Stmt::Function(v) => v.span,
+            Stmt::If(v) => v.span,
+            Stmt::Loop(v) => v.span,
+            Stmt::Match(v) => v.span,
+            Stmt::Print(v) => v.span,
+            Stmt::Return(v) => v.span,
+            Stmt::Var(v) => v.span,
+            Stmt::While(v) => v.span,
+        }
+    }
 }
 
 pub trait StmtVisitor<T> {
     fn visit_block_stmt(&self, stmt: &BlockStmt) -> Result<T, LoxError>;
     fn visit_break_stmt(&self, stmt: &BreakStmt) -> Result<T, LoxError>;
+    fn visit_class_stmt(&self, stmt: &ClassStmt) -> Result<T, LoxError>;
     fn visit_continue_stmt(&self, stmt: &ContinueStmt) -> Result<T, LoxError>;
+    fn visit_do_while_stmt(&self, stmt: &DoWhileStmt) -> Result<T, LoxError>;
     fn visit_expression_stmt(&self, stmt: &ExpressionStmt) -> Result<T, LoxError>;
+    fn visit_for_in_stmt(&self, stmt: &ForInStmt) -> Result<T, LoxError>;
     fn visit_function_stmt(&self, stmt: &FunctionStmt) -> Result<T, LoxError>;
     fn visit_if_stmt(&self, stmt: &IfStmt) -> Result<T, LoxError>;
+    fn visit_loop_stmt(&self, stmt: &LoopStmt) -> Result<T, LoxError>;
+    fn visit_match_stmt(&self, stmt: &MatchStmt) -> Result<T, LoxError>;
     fn visit_print_stmt(&self, stmt: &PrintStmt) -> Result<T, LoxError>;
+    fn visit_return_stmt(&self, stmt: &ReturnStmt) -> Result<T, LoxError>;
     fn visit_var_stmt(&self, stmt: &VarStmt) -> Result<T, LoxError>;
     fn visit_while_stmt(&self, stmt: &WhileStmt) -> Result<T, LoxError>;
 }
 
 pub struct BlockStmt {
     pub statements: Rc<Vec<Rc<Stmt>>>,
+    pub span: Span,
 }
 
 pub struct BreakStmt {
     pub token: Token,
+    pub label: Option<Token>,
+    pub span: Span,
+}
+
+pub struct ClassStmt {
+    pub name: Token,
+    pub superclass: Option<Rc<Expr>>,
+    pub modifiers: Vec<Token>,
+    pub methods: Vec<Rc<FunctionStmt>>,
+    pub fields: Vec<Rc<VarStmt>>,
+    pub span: Span,
 }
 
 pub struct ContinueStmt {
     pub token: Token,
+    pub label: Option<Token>,
+    pub span: Span,
+}
+
+pub struct DoWhileStmt {
+    pub body: Rc<Stmt>,
+    pub condition: Rc<Expr>,
+    pub label: Option<Token>,
+    pub span: Span,
 }
 
 pub struct ExpressionStmt {
     pub expression: Rc<Expr>,
+    pub span: Span,
+}
+
+pub struct ForInStmt {
+    pub name: Token,
+    pub keyword: Token,
+    pub iterable: Rc<Expr>,
+    pub body: Rc<Stmt>,
+    pub label: Option<Token>,
+    pub span: Span,
 }
 
 pub struct FunctionStmt {
     pub name: Token,
-    pub params: Rc<Vec<Token>>,
+    pub modifiers: Vec<Token>,
+    pub params: Rc<Vec<Parameter>>,
     pub body: Rc<Vec<Rc<Stmt>>>,
+    pub span: Span,
 }
 
 pub struct IfStmt {
     pub condition: Rc<Expr>,
     pub then_branch: Rc<Stmt>,
     pub else_branch: Option<Rc<Stmt>>,
+    pub span: Span,
+}
+
+pub struct LoopStmt {
+    pub body: Rc<Stmt>,
+    pub label: Option<Token>,
+    pub span: Span,
+}
+
+pub struct MatchStmt {
+    pub keyword: Token,
+    pub subject: Rc<Expr>,
+    pub arms: Vec<MatchArm<Rc<Stmt>>>,
+    pub span: Span,
 }
 
 pub struct PrintStmt {
     pub expression: Rc<Expr>,
+    pub span: Span,
+}
+
+pub struct ReturnStmt {
+    pub keyword: Token,
+    pub value: Option<Rc<Expr>>,
+    pub span: Span,
 }
 
 pub struct VarStmt {
     pub name: Token,
+    pub modifiers: Vec<Token>,
     pub initializer: Option<Rc<Expr>>,
+    pub span: Span,
 }
 
 pub struct WhileStmt {
     pub condition: Rc<Expr>,
     pub body: Rc<Stmt>,
-    pub is_for_loop: bool,
+    pub increment: Option<Rc<Expr>>,
+    pub label: Option<Token>,
+    pub span: Span,
 }
 
 impl BlockStmt {
@@ -98,18 +197,36 @@ impl BreakStmt {
     }
 }
 
+impl ClassStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_class_stmt(self)
+    }
+}
+
 impl ContinueStmt {
     pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
         visitor.visit_continue_stmt(self)
     }
 }
 
+impl DoWhileStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_do_while_stmt(self)
+    }
+}
+
 impl ExpressionStmt {
     pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
         visitor.visit_expression_stmt(self)
     }
 }
 
+impl ForInStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_for_in_stmt(self)
+    }
+}
+
 impl FunctionStmt {
     pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
         visitor.visit_function_stmt(self)
@@ -122,12 +239,30 @@ impl IfStmt {
     }
 }
 
+impl LoopStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_loop_stmt(self)
+    }
+}
+
+impl MatchStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_match_stmt(self)
+    }
+}
+
 impl PrintStmt {
     pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
         visitor.visit_print_stmt(self)
     }
 }
 
+impl ReturnStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
+        visitor.visit_return_stmt(self)
+    }
+}
+
 impl VarStmt {
     pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxError> {
         visitor.visit_var_stmt(self)
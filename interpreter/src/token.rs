@@ -1,5 +1,6 @@
 use crate::tokentype::*;
 use crate::object::Object;
+use crate::span::Span;
 use std::fmt;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -7,7 +8,7 @@ pub struct Token {
     pub t_type: TokenType,
     pub lexeme: String,
     pub literal: Option<Object>,
-    pub line: usize,
+    pub span: Span,
 }
 
 impl Token {
@@ -15,22 +16,13 @@ impl Token {
         t_type: TokenType, 
         lexeme: String,
         literal: Option<Object>,
-        line: usize,
+        span: Span,
     ) -> Self {
             Token {
                 t_type, 
                 lexeme,
                 literal,
-                line,
-            }
-        }
-
-        pub fn dup(&self) -> Token {
-            Token {
-                t_type: self.t_type,
-                lexeme: self.lexeme.to_string(),
-                literal: self.literal.clone(),
-                line: self.line,
+                span,
             }
         }
 }
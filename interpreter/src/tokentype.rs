@@ -1,24 +1,30 @@
-#![allow(non_camel_case_types)]
+#![allow(non_camel_case_types, clippy::upper_case_acronyms)]
 
 
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub enum TokenType {
       // Single-character tokens.
-  LEFT_PAREN, RIGHT_PAREN, LEFT_BRACE, RIGHT_BRACE,
-  COMMA, DOT, MINUS, PLUS, SEMICOLON, SLASH, STAR,
+  LEFT_PAREN, RIGHT_PAREN, LEFT_BRACE, RIGHT_BRACE, LEFT_BRACKET, RIGHT_BRACKET,
+  COMMA, DOT, MINUS, PLUS, SEMICOLON, SLASH, STAR, COLON,
 
   // One or two character tokens.
+  HASH_LEFT_BRACE, DOT_DOT, DOT_DOT_EQUAL, DOT_DOT_DOT,
   BANG, BANG_EQUAL,
-  EQUAL, EQUAL_EQUAL,
+  EQUAL, EQUAL_EQUAL, EQUAL_GREATER,
   GREATER, GREATER_EQUAL,
   LESS, LESS_EQUAL,
 
   // Literals.
-  IDENTIFIER, STRING, NUMBER,
+  IDENTIFIER, STRING, INTERPOLATION, NUMBER,
 
   // Keywords.
   AND, ELSE, FALSE, FUN, FOR, IF, NIL, OR,
-  PRINT, RETURN, TRUE, VAR, WHILE, BREAK, CONTINUE,
+  PRINT, RETURN, TRUE, VAR, WHILE, BREAK, CONTINUE, MATCH, DO, LOOP, IN,
+  CLASS, SUPER, THIS,
+  PRIVATE, PUBLIC, DYNAMIC, STATIC, OVERRIDE,
+
+  // Stands in for text the scanner already reported.
+  ERROR,
 
   EOF
 }